@@ -0,0 +1,126 @@
+// Memory and time of a boolean index, as posting lists against the
+// linked lists it used to be kept in. Run with `cargo bench`.
+use std::alloc::{ GlobalAlloc, Layout, System };
+use std::sync::atomic::{ AtomicUsize, Ordering };
+use std::time::Instant;
+use std::collections::HashSet;
+use ds::{ LinkedList, Posting };
+use catalogue::{ Catalogue, Kind };
+use scalar::Scalar;
+
+struct Counting;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+const COUNT: u32 = 200_000;
+
+// Layout of catalogue::boolean::Boolean before posting lists
+struct Linked {
+    t: LinkedList<u32>,
+    f: LinkedList<u32>
+}
+
+fn allocated() -> usize {
+    ALLOCATED.load(Ordering::Relaxed)
+}
+
+fn report(name: &str, bytes: usize, add: u128, read: u128, intersect: u128, remove: u128) {
+    println!("{:<8} {:>12} bytes {:>8} us add {:>8} us read {:>8} us intersect {:>8} us remove", name, bytes, add, read, intersect, remove);
+}
+
+fn linked() {
+    let start = allocated();
+    let time = Instant::now();
+    let mut even = Linked { t: LinkedList::new(), f: LinkedList::new() };
+    let mut third = Linked { t: LinkedList::new(), f: LinkedList::new() };
+
+    for id in 0..COUNT {
+        if id % 2 == 0 { even.t.add(id) } else { even.f.add(id) }
+        if id % 3 == 0 { third.t.add(id) } else { third.f.add(id) }
+    }
+
+    let add = time.elapsed().as_micros();
+    let bytes = allocated() - start;
+
+    let time = Instant::now();
+    let ids = even.t.collect();
+    let read = time.elapsed().as_micros();
+
+    let time = Instant::now();
+    let other: HashSet<u32> = third.t.collect().into_iter().collect();
+    let both: Vec<u32> = ids.into_iter().filter(|id| other.contains(id)).collect();
+    let intersect = time.elapsed().as_micros();
+
+    assert_eq!(both.len() as u32, COUNT.div_ceil(6));
+
+    // newest are at the head, so these are the cheapest to remove
+    let time = Instant::now();
+    for id in (COUNT - 1000..COUNT).rev() {
+        if id % 2 == 0 { even.t.remove(id) } else { even.f.remove(id) }
+    }
+    let remove = time.elapsed().as_micros();
+
+    report("linked", bytes, add, read, intersect, remove);
+
+    // dropping nodes one by one keeps the stack flat
+    for list in [&mut even.t, &mut even.f, &mut third.t, &mut third.f] {
+        for id in list.collect() {
+            list.remove(id);
+        }
+    }
+}
+
+fn posting() {
+    let start = allocated();
+    let time = Instant::now();
+    let mut catalogue: Catalogue<u8> = Catalogue::new();
+    catalogue.setup(1, Kind::Plain);
+    catalogue.setup(2, Kind::Plain);
+
+    for id in 0..COUNT {
+        catalogue.add(1, Scalar::Boolean(id % 2 == 0), id);
+        catalogue.add(2, Scalar::Boolean(id % 3 == 0), id);
+    }
+
+    let add = time.elapsed().as_micros();
+    let bytes = allocated() - start;
+
+    let time = Instant::now();
+    let even: Posting = catalogue.get(1, Scalar::Boolean(true));
+    let read = time.elapsed().as_micros();
+
+    let time = Instant::now();
+    let both = even.intersect(&catalogue.get(2, Scalar::Boolean(true)));
+    let intersect = time.elapsed().as_micros();
+
+    assert_eq!(both.len() as u32, COUNT.div_ceil(6));
+
+    let time = Instant::now();
+    for id in (COUNT - 1000..COUNT).rev() {
+        catalogue.remove(1, Scalar::Boolean(id % 2 == 0), id);
+    }
+    let remove = time.elapsed().as_micros();
+
+    report("posting", bytes, add, read, intersect, remove);
+}
+
+fn main() {
+    println!("two boolean indexes over {} ids", COUNT);
+    linked();
+    posting();
+}
@@ -1,22 +1,22 @@
-use ds::LinkedList;
+use ds::Posting;
 use crate::CatalogueTrait;
 
-pub struct Boolean<T>{
-    t: LinkedList<T>,
-    f: LinkedList<T>
+pub struct Boolean {
+    t: Posting,
+    f: Posting
 }
 
-impl<T: Eq + Copy> Boolean<T> {
+impl Boolean {
     pub fn new() -> Self {
         Self {
-            t: LinkedList::new(),
-            f: LinkedList::new()
+            t: Posting::new(),
+            f: Posting::new()
         }
     }
 }
 
-impl<T: Eq + Copy> CatalogueTrait<bool, T> for Boolean<T> {
-    fn add(&mut self, key: bool, value: T) {
+impl CatalogueTrait<bool> for Boolean {
+    fn add(&mut self, key: bool, value: u32) {
         if key == true {
             self.t.add(value)
         }else {
@@ -24,7 +24,7 @@ impl<T: Eq + Copy> CatalogueTrait<bool, T> for Boolean<T> {
         }
     }
 
-    fn remove(&mut self, key: bool, value: T) {
+    fn remove(&mut self, key: bool, value: u32) {
         if key == true {
             self.t.remove(value)
         }else {
@@ -32,12 +32,17 @@ impl<T: Eq + Copy> CatalogueTrait<bool, T> for Boolean<T> {
         }
     }
 
-    fn read(&self, key: bool) -> Vec<T> {
+    fn read(&self, key: bool) -> Posting {
         if key == true {
-            self.t.collect()
+            self.t.clone()
         }else {
-            self.f.collect()
+            self.f.clone()
         }
     }
+
+    fn entries(&self) -> Vec<(bool, Posting)> {
+        vec![(true, self.t.clone()), (false, self.f.clone())]
+    }
 }
 
+
@@ -0,0 +1,224 @@
+/// Compound indexes an ordered tuple of fields under one composite key.
+/// Keys sort field by field, so equality on leading fields plus a
+/// range on the next one reads a single continuous run of keys.
+use std::collections::BTreeMap;
+use std::ops::Bound;
+use ds::Posting;
+use scalar::{ Scalar, Numeric };
+use crate::def::{ differ, Difference };
+
+// Variant order is the key order. Min and Max never get stored,
+// they only pad bounds so that a bound covers all keys after it.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Part {
+    Min,
+    Absent,
+    Boolean(bool),
+    Text(String),
+    // Integer and Decimal share one ordered keyspace
+    Number(Numeric),
+    Max
+}
+
+impl Part {
+    fn from_scalar(scalar: &Scalar) -> Self {
+        match scalar {
+            Scalar::Boolean(v) => Self::Boolean(*v),
+            Scalar::Text(v) => Self::Text(v.clone()),
+            Scalar::Integer(v) => Self::Number(v.to_numeric()),
+            Scalar::Decimal(v) => Self::Number(v.to_numeric())
+        }
+    }
+}
+
+pub struct Compound<F> {
+    fields: Vec<F>,
+    space: BTreeMap<Vec<Part>, Posting>
+}
+
+impl<F: Copy + PartialEq> Compound<F> {
+    pub fn new(fields: Vec<F>) -> Self {
+        Self {
+            fields,
+            space: BTreeMap::new()
+        }
+    }
+
+    pub fn get_fields(&self) -> &[F] {
+        &self.fields
+    }
+
+    // What this holds against what other, built from the records, holds
+    pub fn compare(&self, expected: &Self) -> Difference {
+        let entries = |compound: &Self| compound.space.iter().map(|(key, posting)| (key.clone(), posting.clone())).collect();
+
+        differ(entries(self), entries(expected))
+    }
+
+    // A record holding none of the fields is left out
+    fn to_key(values: &[Option<Scalar>]) -> Option<Vec<Part>> {
+        if values.iter().all(|o_value| o_value.is_none()) {
+            return None;
+        }
+
+        let key = values.iter().map(|o_value| {
+            match o_value {
+                Some(value) => Part::from_scalar(value),
+                None => Part::Absent
+            }
+        }).collect();
+
+        Some(key)
+    }
+
+    // values are in the order of fields
+    pub fn add(&mut self, values: &[Option<Scalar>], value: u32) {
+        if let Some(key) = Self::to_key(values) {
+            let o_posting = self.space.get_mut(&key);
+
+            if let Some(posting) = o_posting {
+                posting.add(value)
+            }else {
+                let mut posting = Posting::new();
+                posting.add(value);
+                self.space.insert(key, posting);
+            }
+        }
+    }
+
+    pub fn remove(&mut self, values: &[Option<Scalar>], value: u32) {
+        if let Some(key) = Self::to_key(values) {
+            let o_posting = self.space.get_mut(&key);
+
+            if let Some(posting) = o_posting {
+                posting.remove(value);
+
+                if posting.is_empty() {
+                    self.space.remove(&key);
+                }
+            }
+        }
+    }
+
+    fn padded(prefix: &[Part], last: Part, padding: Part, len: usize) -> Vec<Part> {
+        let mut key = prefix.to_vec();
+
+        key.push(last);
+        key.resize(len, padding);
+        key
+    }
+
+    // Equality on prefix fields and a range on the field right after them.
+    // Values of other types than the range may be included, so
+    // caller should check the records it reads.
+    pub fn read(&self, prefix: &[Scalar], lower: Bound<Scalar>, upper: Bound<Scalar>) -> Posting {
+        let len = self.fields.len();
+        let mut values: Vec<u32> = Vec::new();
+
+        if prefix.len() > len {
+            return Posting::new();
+        }
+
+        let prefix: Vec<Part> = prefix.iter().map(Part::from_scalar).collect();
+
+        // nothing left to take a range on
+        let (lower, upper) = if prefix.len() == len {
+            (Bound::Unbounded, Bound::Unbounded)
+        }else {
+            (lower, upper)
+        };
+        let is_last = prefix.len() + 1 == len;
+
+        let lower_key = match lower {
+            Bound::Included(v) => Bound::Included(Self::padded(&prefix, Part::from_scalar(&v), Part::Min, len)),
+            Bound::Excluded(v) if is_last => Bound::Excluded(Self::padded(&prefix, Part::from_scalar(&v), Part::Min, len)),
+            Bound::Excluded(v) => Bound::Included(Self::padded(&prefix, Part::from_scalar(&v), Part::Max, len)),
+            Bound::Unbounded => Bound::Included(Self::padded(&prefix, Part::Min, Part::Min, len))
+        };
+        let upper_key = match upper {
+            Bound::Included(v) => Bound::Included(Self::padded(&prefix, Part::from_scalar(&v), Part::Max, len)),
+            Bound::Excluded(v) => Bound::Excluded(Self::padded(&prefix, Part::from_scalar(&v), Part::Min, len)),
+            Bound::Unbounded => Bound::Included(Self::padded(&prefix, Part::Max, Part::Max, len))
+        };
+
+        // BTreeMap panics on a reversed range
+        let (lower_inner, upper_inner) = match (&lower_key, &upper_key) {
+            (Bound::Included(l) | Bound::Excluded(l), Bound::Included(u) | Bound::Excluded(u)) => (l, u),
+            _ => return Posting::new()
+        };
+
+        if lower_inner > upper_inner
+        || (lower_inner == upper_inner && (matches!(lower_key, Bound::Excluded(_)) || matches!(upper_key, Bound::Excluded(_)))) {
+            return Posting::new();
+        }
+
+        for (_, posting) in self.space.range((lower_key, upper_key)) {
+            values.extend(posting.collect())
+        }
+
+        Posting::from_vec(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scalar::{ Integer, Decimal };
+
+    fn text(s: &str) -> Option<Scalar> {
+        Some(Scalar::Text(s.to_owned()))
+    }
+
+    fn integer(value: isize) -> Option<Scalar> {
+        Some(Scalar::Integer(Integer::new(value)))
+    }
+
+    fn sorted(posting: Posting) -> Vec<u32> {
+        posting.collect()
+    }
+
+    fn compound() -> Compound<u8> {
+        let mut compound: Compound<u8> = Compound::new(vec![1, 2]);
+
+        compound.add(&[text("acme"), integer(10)], 0);
+        compound.add(&[text("acme"), integer(20)], 1);
+        compound.add(&[text("acme"), integer(30)], 2);
+        compound.add(&[text("bolt"), integer(20)], 3);
+        compound.add(&[text("acme"), None], 4);
+        compound.add(&[None, None], 5);
+        compound
+    }
+
+    #[test]
+    fn prefix_and_range() {
+        let compound = compound();
+        let acme = vec![text("acme").unwrap()];
+        let twenty = integer(20).unwrap();
+
+        assert_eq!(sorted(compound.read(&acme, Bound::Excluded(twenty.clone()), Bound::Unbounded)), vec![2]);
+        assert_eq!(sorted(compound.read(&acme, Bound::Included(twenty.clone()), Bound::Unbounded)), vec![1, 2]);
+        assert_eq!(sorted(compound.read(&acme, Bound::Unbounded, Bound::Excluded(twenty.clone()))), vec![0, 4]);
+        assert_eq!(sorted(compound.read(&acme, Bound::Unbounded, Bound::Unbounded)), vec![0, 1, 2, 4]);
+        assert_eq!(sorted(compound.read(&[], Bound::Included(text("bolt").unwrap()), Bound::Included(text("bolt").unwrap()))), vec![3]);
+    }
+
+    #[test]
+    fn full_key() {
+        let compound = compound();
+        let key = vec![text("acme").unwrap(), Scalar::Decimal(Decimal::from_string("20.0".to_owned()).unwrap())];
+
+        assert_eq!(compound.read(&key, Bound::Unbounded, Bound::Unbounded).collect(), vec![1]);
+    }
+
+    #[test]
+    fn remove() {
+        let mut compound = compound();
+        let acme = vec![text("acme").unwrap()];
+
+        compound.remove(&[text("acme"), integer(20)], 1);
+        compound.remove(&[text("acme"), None], 4);
+
+        assert_eq!(sorted(compound.read(&acme, Bound::Unbounded, Bound::Unbounded)), vec![0, 2]);
+        assert!(compound.read(&acme, Bound::Excluded(integer(30).unwrap()), Bound::Unbounded).is_empty());
+    }
+}
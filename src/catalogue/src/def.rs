@@ -1,5 +1,85 @@
-pub trait CatalogueTrait<K, V> {
-    fn add(&mut self, key: K, value: V);
-    fn remove(&mut self, key: K, value: V);
-    fn read(&self, key: K) -> Vec<V>;
-}
\ No newline at end of file
+use ds::Posting;
+
+// Values are the u32 identifiers of records
+pub trait CatalogueTrait<K> {
+    fn add(&mut self, key: K, value: u32);
+    fn remove(&mut self, key: K, value: u32);
+    fn read(&self, key: K) -> Posting;
+    // Every key with its identifiers
+    fn entries(&self) -> Vec<(K, Posting)>;
+}
+
+// Identifiers missing from the index and
+// identifiers it holds which it should not
+pub type Difference = (Posting, Posting);
+
+// Walks both sets of entries in key order, the first one
+// being what the index holds and the second what it should.
+pub fn differ<K: Ord>(mut live: Vec<(K, Posting)>, mut expected: Vec<(K, Posting)>) -> Difference {
+    let mut missing = Posting::new();
+    let mut stale = Posting::new();
+
+    live.sort_by(|a, b| a.0.cmp(&b.0));
+    expected.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut live = live.into_iter().peekable();
+    let mut expected = expected.into_iter().peekable();
+
+    loop {
+        match (live.peek(), expected.peek()) {
+            (None, None) => break,
+            (Some(_), None) => stale = stale.union(&live.next().unwrap().1),
+            (None, Some(_)) => missing = missing.union(&expected.next().unwrap().1),
+            (Some((l, _)), Some((e, _))) => {
+                if l < e {
+                    stale = stale.union(&live.next().unwrap().1);
+                }else if l > e {
+                    missing = missing.union(&expected.next().unwrap().1);
+                }else {
+                    let (_, l) = live.next().unwrap();
+                    let (_, e) = expected.next().unwrap();
+
+                    missing = missing.union(&e.difference(&l));
+                    stale = stale.union(&l.difference(&e));
+                }
+            }
+        }
+    }
+
+    (missing, stale)
+}
+
+const PLAIN: u8 = 0;
+const UNIQUE: u8 = 1;
+const FULL_TEXT: u8 = 2;
+const FULL_TEXT_STEM: u8 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    Plain,
+    // At most one identifier per key
+    Unique,
+    // Words of text for search, stemmed when true
+    FullText(bool)
+}
+
+impl Kind {
+    pub fn get_value(&self) -> u8 {
+        match self {
+            Self::Plain => PLAIN,
+            Self::Unique => UNIQUE,
+            Self::FullText(false) => FULL_TEXT,
+            Self::FullText(true) => FULL_TEXT_STEM
+        }
+    }
+
+    pub fn get_type(value: u8) -> Option<Self> {
+        match value {
+            PLAIN => Some(Self::Plain),
+            UNIQUE => Some(Self::Unique),
+            FULL_TEXT => Some(Self::FullText(false)),
+            FULL_TEXT_STEM => Some(Self::FullText(true)),
+            _ => None
+        }
+    }
+}
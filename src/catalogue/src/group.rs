@@ -1,27 +1,47 @@
-use crate::def::CatalogueTrait;
+use crate::def::{ CatalogueTrait, Kind, Difference, differ };
 use crate::boolean::Boolean;
 use crate::text::Text;
 use crate::integer::Integer;
-use scalar::Scalar;
+use crate::inverted::Inverted;
+use std::ops::Bound;
+use scalar::{ Scalar, Numeric };
+use ds::Posting;
 
-enum Individual<T> {
-    Boolean(Boolean<T>),
-    Text(Text<T>),
-    Integer(Integer<isize, T>)
+enum Individual {
+    Boolean(Boolean),
+    Text(Text),
+    // Integer and Decimal share this bucket as one ordered keyspace
+    Integer(Integer<Numeric>)
 }
 
-pub struct Group<T>([Individual<T>; 3]);
+// Text of a full text group goes to the inverted index
+// in place of the text individual, other types stay as they are.
+pub struct Group([Individual; 3], Kind, Option<Inverted<u32>>);
+
+impl Group {
+    pub fn new(kind: Kind) -> Self {
+        let o_inverted = match kind {
+            Kind::FullText(stem) => Some(Inverted::new(stem)),
+            _ => None
+        };
 
-impl<T: Eq + Copy> Group<T> {
-    pub fn new() -> Self {
         Self([
             Individual::Boolean(Boolean::new()),
             Individual::Text(Text::new()),
             Individual::Integer(Integer::new())
-        ])
+        ], kind, o_inverted)
+    }
+
+    pub fn get_kind(&self) -> Kind {
+        self.1
     }
 
-    pub fn add(&mut self, key: Scalar, value: T){
+    pub fn add(&mut self, key: Scalar, value: u32){
+        if let (Some(inverted), Scalar::Text(k)) = (self.2.as_mut(), &key) {
+            inverted.add(k, value);
+            return;
+        }
+
         match key {
             Scalar::Boolean(k) => {
                 let index_value = &mut self.0[0];
@@ -48,7 +68,7 @@ impl<T: Eq + Copy> Group<T> {
                 
                 match index_value {
                     Individual::Integer(v) => {
-                        v.add(k.unwrap(), value)
+                        v.add(k.to_numeric(), value)
                     },
                     _ => {}
                 }
@@ -58,7 +78,7 @@ impl<T: Eq + Copy> Group<T> {
                 
                 match index_value {
                     Individual::Integer(v) => {
-                        v.add(k.to_integer(), value)
+                        v.add(k.to_numeric(), value)
                     },
                     _ => {}
                 }
@@ -66,7 +86,12 @@ impl<T: Eq + Copy> Group<T> {
         }
     }
 
-    pub fn remove(&mut self, key: Scalar, value: T){
+    pub fn remove(&mut self, key: Scalar, value: u32){
+        if let (Some(inverted), Scalar::Text(k)) = (self.2.as_mut(), &key) {
+            inverted.remove(k, value);
+            return;
+        }
+
         match key {
             Scalar::Boolean(k) => {
                 let index_value = &mut self.0[0];
@@ -93,7 +118,7 @@ impl<T: Eq + Copy> Group<T> {
                 
                 match index_value {
                     Individual::Integer(v) => {
-                        v.remove(k.unwrap(), value)
+                        v.remove(k.to_numeric(), value)
                     },
                     _ => {}
                 }
@@ -103,7 +128,7 @@ impl<T: Eq + Copy> Group<T> {
                 
                 match index_value {
                     Individual::Integer(v) => {
-                        v.remove(k.to_integer(), value)
+                        v.remove(k.to_numeric(), value)
                     },
                     _ => {}
                 }
@@ -111,7 +136,7 @@ impl<T: Eq + Copy> Group<T> {
         }
     }
 
-    pub fn read(&self, key: Scalar) -> Vec<T>{
+    pub fn read(&self, key: Scalar) -> Posting{
         match key {
             Scalar::Boolean(k) => {
                 let index_value = &self.0[0];
@@ -121,7 +146,7 @@ impl<T: Eq + Copy> Group<T> {
                         v.read(k)
                     },
                     _ => {
-                        vec![]
+                        Posting::new()
                     }
                 }
             },
@@ -133,7 +158,7 @@ impl<T: Eq + Copy> Group<T> {
                         v.read(k)
                     },
                     _ => {
-                        vec![]
+                        Posting::new()
                     }
                 }
             },
@@ -142,10 +167,10 @@ impl<T: Eq + Copy> Group<T> {
                 
                 match index_value {
                     Individual::Integer(v) => {
-                        v.read(k.unwrap())
+                        v.read(k.to_numeric())
                     },
                     _ => {
-                        vec![]
+                        Posting::new()
                     }
                 }
             },
@@ -154,13 +179,76 @@ impl<T: Eq + Copy> Group<T> {
                 
                 match index_value {
                     Individual::Integer(v) => {
-                        v.read(k.to_integer())
+                        v.read(k.to_numeric())
                     },
                     _ => {
-                        vec![]
+                        Posting::new()
                     }
                 }
             }
         }
     }
-}
\ No newline at end of file
+
+    // What this holds against what expected, built from the records, holds
+    pub fn compare(&self, expected: &Group) -> Difference {
+        let mut missing = Posting::new();
+        let mut stale = Posting::new();
+
+        for (live, expected) in self.0.iter().zip(expected.0.iter()) {
+            let (m, s) = match (live, expected) {
+                (Individual::Boolean(l), Individual::Boolean(e)) => differ(l.entries(), e.entries()),
+                (Individual::Text(l), Individual::Text(e)) => differ(l.entries(), e.entries()),
+                (Individual::Integer(l), Individual::Integer(e)) => differ(l.entries(), e.entries()),
+                _ => continue
+            };
+
+            missing = missing.union(&m);
+            stale = stale.union(&s);
+        }
+
+        if let (Some(live), Some(expected)) = (&self.2, &expected.2) {
+            let to_postings = |entries: Vec<((String, u32), Vec<u32>)>| entries.into_iter().map(|(key, values)| (key, Posting::from_vec(values))).collect();
+            let (m, s) = differ(to_postings(live.entries()), to_postings(expected.entries()));
+
+            missing = missing.union(&m);
+            stale = stale.union(&s);
+        }
+
+        (missing, stale)
+    }
+
+    // None when group is not a full text one
+    pub fn search(&self, query: &str) -> Option<Vec<u32>> {
+        self.2.as_ref().map(|inverted| inverted.search(query))
+    }
+
+    // Only numeric keys have an order in the index.
+    // None tells that range can't be served from here.
+    pub fn read_range(&self, lower: Bound<Scalar>, upper: Bound<Scalar>) -> Option<Posting> {
+        let o_lower = to_numeric_bound(lower);
+        let o_upper = to_numeric_bound(upper);
+
+        if o_lower.is_none() || o_upper.is_none() {
+            return None;
+        }
+
+        let lower = o_lower.unwrap();
+        let upper = o_upper.unwrap();
+        let index_value = &self.0[2];
+
+        match index_value {
+            Individual::Integer(v) => {
+                Some(v.read_range(lower.as_ref(), upper.as_ref()))
+            },
+            _ => None
+        }
+    }
+}
+
+fn to_numeric_bound(bound: Bound<Scalar>) -> Option<Bound<Numeric>> {
+    match bound {
+        Bound::Included(scalar) => scalar.to_numeric().map(Bound::Included),
+        Bound::Excluded(scalar) => scalar.to_numeric().map(Bound::Excluded),
+        Bound::Unbounded => Some(Bound::Unbounded)
+    }
+}
@@ -1,49 +1,69 @@
-
 use std::collections::BTreeMap;
-use ds::LinkedList;
+use std::ops::Bound;
+use ds::Posting;
 use crate::def::CatalogueTrait;
 
-pub struct Integer<K, V> {
-    space: BTreeMap<K, LinkedList<V>>
+pub struct Integer<K> {
+    space: BTreeMap<K, Posting>
 }
 
-impl<K: Ord, V: Eq + Copy> Integer<K, V>{
+impl<K: Ord> Integer<K>{
     pub fn new() -> Self {
         Self {
             space: BTreeMap::new()
         }
     }
+
+    // Keys are kept in order, so a range is read without
+    // looking at any key outside of it.
+    pub fn read_range(&self, lower: Bound<&K>, upper: Bound<&K>) -> Posting {
+        let mut values: Vec<u32> = Vec::new();
+
+        for (_, posting) in self.space.range::<K, _>((lower, upper)) {
+            values.extend(posting.collect())
+        }
+
+        Posting::from_vec(values)
+    }
 }
 
-impl<K: Ord, V: Eq + Copy> CatalogueTrait<K, V> for Integer<K, V> {
-    fn add(&mut self, key: K, value: V) {
+impl<K: Ord + Clone> CatalogueTrait<K> for Integer<K> {
+    fn add(&mut self, key: K, value: u32) {
         let has_key = self.space.contains_key(&key);
 
         if has_key == false {
-            let mut ll: LinkedList<V> = LinkedList::new();
-            ll.add(value);
-            self.space.insert(key, ll);
+            let mut posting = Posting::new();
+            posting.add(value);
+            self.space.insert(key, posting);
         }else {
-            let ll = self.space.get_mut(&key).unwrap();
-            ll.add(value)
+            let posting = self.space.get_mut(&key).unwrap();
+            posting.add(value)
         }
     }
 
-    fn remove(&mut self, key: K, value: V) {
-        let o_ll = self.space.get_mut(&key);
+    fn remove(&mut self, key: K, value: u32) {
+        let o_posting = self.space.get_mut(&key);
+
+        if let Some(posting) = o_posting {
+            posting.remove(value);
 
-        if let Some(ll) = o_ll {
-            ll.remove(value)
+            if posting.is_empty() {
+                self.space.remove(&key);
+            }
         }
     }
 
-    fn read(&self, key: K) -> Vec<V> {
-        let o_ll = self.space.get(&key);
+    fn read(&self, key: K) -> Posting {
+        let o_posting = self.space.get(&key);
 
-        if let Some(ll) = o_ll {
-            ll.collect()
+        if let Some(posting) = o_posting {
+            posting.clone()
         }else {
-            vec![]
+            Posting::new()
         }
     }
-}
\ No newline at end of file
+
+    fn entries(&self) -> Vec<(K, Posting)> {
+        self.space.iter().map(|(key, posting)| (key.clone(), posting.clone())).collect()
+    }
+}
@@ -0,0 +1,326 @@
+/// Inverted index of words in text, for keyword search.
+/// Text is split on anything other than letters and digits
+/// and lowercased, words may further be cut down to stems.
+/// A query holds words which all must be present, and
+/// "or" between such groups lets any one group match.
+use std::collections::{ HashMap, HashSet };
+use std::hash::Hash;
+
+// BM25 tuning, the usual defaults
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+const OR: &str = "or";
+
+pub fn tokenize(text: &str, stem: bool) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let word = word.to_lowercase();
+
+            if stem {
+                to_stem(&word)
+            }else {
+                word
+            }
+        })
+        .collect()
+}
+
+// Strips a few common English suffixes. It is not a full stemmer,
+// only enough for plurals and -ing/-ed forms to meet their word.
+fn to_stem(word: &str) -> String {
+    let chars: Vec<char> = word.chars().collect();
+    let len = chars.len();
+
+    // short words are left as they are
+    if len <= 3 {
+        return word.to_owned();
+    }
+
+    let ends = |suffix: &str| word.ends_with(suffix);
+    let cut = |n: usize| chars[..len - n].iter().collect::<String>();
+
+    let stem = if ends("sses") {
+        cut(2)
+    }else if ends("ies") {
+        cut(3) + "y"
+    }else if ends("ing") && len > 5 {
+        cut(3)
+    }else if (ends("ed") || ends("ly")) && len > 4 {
+        cut(2)
+    }else if ends("s") && !ends("ss") && !ends("us") {
+        cut(1)
+    }else {
+        return word.to_owned();
+    };
+
+    // running -> runn -> run
+    let stem_chars: Vec<char> = stem.chars().collect();
+    let stem_len = stem_chars.len();
+
+    if stem_len > 2
+    && stem_chars[stem_len - 1] == stem_chars[stem_len - 2]
+    && !"aeioulsz".contains(stem_chars[stem_len - 1]) {
+        stem_chars[..stem_len - 1].iter().collect()
+    }else {
+        stem
+    }
+}
+
+// Groups of words where any group should match in full
+fn parse_query(query: &str, stem: bool) -> Vec<Vec<String>> {
+    let mut groups: Vec<Vec<String>> = vec![Vec::new()];
+
+    for word in tokenize(query, false) {
+        if word == OR {
+            groups.push(Vec::new());
+        }else if stem {
+            groups.last_mut().unwrap().push(to_stem(&word));
+        }else {
+            groups.last_mut().unwrap().push(word);
+        }
+    }
+
+    groups.retain(|group| !group.is_empty());
+    groups
+}
+
+// Used when no index serves the query, so the text is looked at directly
+pub fn matches(text: &str, query: &str, stem: bool) -> bool {
+    let words = tokenize(text, stem);
+
+    parse_query(query, stem).iter().any(|group| group.iter().all(|word| words.contains(word)))
+}
+
+pub struct Inverted<T> {
+    stem: bool,
+    // word to identifiers and how often it occurs in each
+    postings: HashMap<String, HashMap<T, u32>>,
+    // word count of every indexed text
+    lengths: HashMap<T, u32>,
+    total_length: u64
+}
+
+impl<T: Eq + Hash + Copy> Inverted<T> {
+    pub fn new(stem: bool) -> Self {
+        Self {
+            stem,
+            postings: HashMap::new(),
+            lengths: HashMap::new(),
+            total_length: 0
+        }
+    }
+
+    pub fn add(&mut self, text: &str, value: T) {
+        let words = tokenize(text, self.stem);
+
+        if words.is_empty() {
+            return;
+        }
+
+        self.total_length += words.len() as u64;
+        *self.lengths.entry(value).or_insert(0) += words.len() as u32;
+
+        for word in words {
+            *self.postings.entry(word).or_default().entry(value).or_insert(0) += 1;
+        }
+    }
+
+    pub fn remove(&mut self, text: &str, value: T) {
+        let words = tokenize(text, self.stem);
+
+        if words.is_empty() || !self.lengths.contains_key(&value) {
+            return;
+        }
+
+        // Text given may not be the text indexed, so only
+        // words found in the postings count as removed.
+        let mut removed: u32 = 0;
+
+        for word in &words {
+            if let Some(posting) = self.postings.get_mut(word) {
+                if let Some(count) = posting.get_mut(&value) {
+                    *count -= 1;
+                    removed += 1;
+
+                    if *count == 0 {
+                        posting.remove(&value);
+                    }
+                }
+
+                if posting.is_empty() {
+                    self.postings.remove(word);
+                }
+            }
+        }
+
+        let length = self.lengths.get_mut(&value).unwrap();
+        *length = length.saturating_sub(removed);
+
+        if *length == 0 {
+            self.lengths.remove(&value);
+        }
+
+        self.total_length = self.total_length.saturating_sub(removed as u64);
+    }
+
+    // Identifiers matching the query, best scored first
+    pub fn search(&self, query: &str) -> Vec<T> {
+        let groups = parse_query(query, self.stem);
+        let mut found: HashSet<T> = HashSet::new();
+
+        for group in &groups {
+            let mut o_matched: Option<Vec<T>> = None;
+
+            // intersect, starting from the rarest word
+            let mut postings: Vec<Option<&HashMap<T, u32>>> = group.iter().map(|word| self.postings.get(word)).collect();
+            postings.sort_by_key(|o_posting| o_posting.map(|posting| posting.len()).unwrap_or(0));
+
+            for o_posting in postings {
+                match o_posting {
+                    None => {
+                        o_matched = Some(Vec::new());
+                        break;
+                    },
+                    Some(posting) => {
+                        o_matched = match o_matched {
+                            None => Some(posting.keys().copied().collect()),
+                            Some(matched) => Some(matched.into_iter().filter(|value| posting.contains_key(value)).collect())
+                        };
+                    }
+                }
+            }
+
+            found.extend(o_matched.unwrap_or_default());
+        }
+
+        let mut words: Vec<&String> = groups.iter().flatten().collect();
+        words.sort();
+        words.dedup();
+
+        let mut result: Vec<(T, f64)> = found.into_iter().map(|value| (value, self.score(&words, value))).collect();
+
+        result.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        result.into_iter().map(|(value, _)| value).collect()
+    }
+
+    // Identifiers of every word, apart by how often the word occurs
+    pub fn entries(&self) -> Vec<((String, u32), Vec<T>)> {
+        let mut entries: HashMap<(String, u32), Vec<T>> = HashMap::new();
+
+        for (word, posting) in &self.postings {
+            for (value, count) in posting {
+                entries.entry((word.clone(), *count)).or_default().push(*value);
+            }
+        }
+
+        entries.into_iter().collect()
+    }
+
+    // BM25 over every query word present in the text
+    fn score(&self, words: &[&String], value: T) -> f64 {
+        let total = self.lengths.len() as f64;
+        let average = self.total_length as f64 / total;
+        let length = *self.lengths.get(&value).unwrap() as f64;
+        let mut score = 0.0;
+
+        for word in words {
+            if let Some(posting) = self.postings.get(*word) {
+                if let Some(count) = posting.get(&value) {
+                    let count = *count as f64;
+                    let found = posting.len() as f64;
+                    let idf = (1.0 + (total - found + 0.5) / (found + 0.5)).ln();
+
+                    score += idf * count * (K1 + 1.0) / (count + K1 * (1.0 - B + B * length / average));
+                }
+            }
+        }
+
+        score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inverted(stem: bool) -> Inverted<u32> {
+        let mut inverted = Inverted::new(stem);
+
+        inverted.add("Red running shoes", 0);
+        inverted.add("Blue shoes, red laces", 1);
+        inverted.add("Red red red hat", 2);
+        inverted.add("A green jacket for runners", 3);
+        inverted
+    }
+
+    fn sorted(mut values: Vec<u32>) -> Vec<u32> {
+        values.sort();
+        values
+    }
+
+    #[test]
+    fn tokenize_and_stem() {
+        assert_eq!(tokenize("Hello, World-wide 42!", false), vec!["hello", "world", "wide", "42"]);
+        assert_eq!(tokenize("running shoes glasses ponies stopped quickly", true), vec!["run", "shoe", "glass", "pony", "stop", "quick"]);
+        assert_eq!(tokenize("bus is red", true), vec!["bus", "is", "red"]);
+    }
+
+    #[test]
+    fn and_or() {
+        let stemmed = inverted(true);
+        let inverted = inverted(false);
+
+        assert_eq!(sorted(inverted.search("red shoes")), vec![0, 1]);
+        assert_eq!(sorted(inverted.search("hat or jacket")), vec![2, 3]);
+        assert_eq!(sorted(inverted.search("blue laces or green")), vec![1, 3]);
+        assert!(inverted.search("red purple").is_empty());
+        assert!(inverted.search("").is_empty());
+        assert!(inverted.search("run").is_empty());
+        assert_eq!(stemmed.search("run"), vec![0]);
+    }
+
+    #[test]
+    fn ranking() {
+        let inverted = inverted(false);
+
+        // more occurrences in a short text rank first
+        assert_eq!(inverted.search("red")[0], 2);
+        // a rare word weighs more than a common one
+        assert_eq!(inverted.search("red or laces")[0], 1);
+    }
+
+    #[test]
+    fn remove() {
+        let mut inverted = inverted(false);
+
+        inverted.remove("Red red red hat", 2);
+        inverted.remove("Blue shoes, red laces", 1);
+
+        assert_eq!(inverted.search("red"), vec![0]);
+        assert!(inverted.search("hat").is_empty());
+        assert_eq!(inverted.total_length, 8);
+
+        // removing what is not there is harmless
+        inverted.remove("Red red red hat", 2);
+
+        assert_eq!(inverted.search("red"), vec![0]);
+
+        // and so is text longer than what was indexed
+        inverted.remove("Red running shoes and socks and more", 0);
+        inverted.remove("Green jacket", 3);
+
+        assert!(inverted.search("red").is_empty());
+        assert_eq!(inverted.lengths.get(&3), Some(&3));
+        assert_eq!(inverted.total_length, 3);
+    }
+
+    #[test]
+    fn scan_matches() {
+        assert!(matches("Red running shoes", "shoes red", false));
+        assert!(matches("Red running shoes", "boots or running", false));
+        assert!(!matches("Red running shoes", "run", false));
+        assert!(matches("Red running shoes", "run", true));
+        assert!(!matches("Red running shoes", "", false));
+    }
+}
@@ -3,26 +3,62 @@ mod boolean;
 mod integer;
 mod text;
 mod group;
+mod compound;
+mod inverted;
+use std::ops::Bound;
 use group::Group;
+use compound::Compound;
 use scalar::Scalar;
 use estate::WholeNumber;
 use warehouse::Inventory;
 use def::CatalogueTrait;
+use ds::Posting;
+pub use def::{ Kind, Difference };
+pub use inverted::matches;
 
-pub struct Catalogue<Field: WholeNumber, Identifier>(Inventory<Field, Group<Identifier>>);
+// Identifiers are record ids, kept in compact posting lists
+pub struct Catalogue<Field: WholeNumber>(Inventory<Field, Group>, Vec<Compound<Field>>);
 
-impl<Field: WholeNumber, Identifier: Eq + Clone + Copy> Catalogue<Field, Identifier> {
+impl<Field: WholeNumber> Catalogue<Field> {
     pub fn new() -> Self{
-        Self(Inventory::new())
+        Self(Inventory::new(), Vec::new())
     }
 
-    pub fn setup(&mut self, field: Field) {
+    pub fn setup(&mut self, field: Field, kind: Kind) {
         if self.0.get(field).is_some() {
             panic!("Field is already indexed")
         }
 
         self.0.reserve(Some(field));
-        self.0.replace(field, Group::new())
+        self.0.replace(field, Group::new(kind))
+    }
+
+    pub fn get_kind(&self, field: Field) -> Option<Kind> {
+        self.0.get(field).map(|group| group.get_kind())
+    }
+
+    // Tells if a unique index already holds the value for any
+    // other identifier. Plain indexes never conflict.
+    pub fn conflicts(&self, field: Field, scalar: Scalar, o_meta: Option<u32>) -> bool {
+        match self.0.get(field) {
+            Some(group) if group.get_kind() == Kind::Unique => {
+                let posting = group.read(scalar);
+
+                match o_meta {
+                    Some(meta) if posting.contains(meta) => posting.len() > 1,
+                    _ => !posting.is_empty()
+                }
+            },
+            _ => false
+        }
+    }
+
+    pub fn drop(&mut self, field: Field) {
+        if !self.has_index(field) {
+            panic!("Field is not indexed")
+        }
+
+        self.0.take(field);
     }
 
     pub fn has_index(&self, field: Field) -> bool {
@@ -35,7 +71,7 @@ impl<Field: WholeNumber, Identifier: Eq + Clone + Copy> Catalogue<Field, Identif
         }
     }
 
-    pub fn add(&mut self, field: Field, scalar: Scalar, meta: Identifier){
+    pub fn add(&mut self, field: Field, scalar: Scalar, meta: u32){
         let o_group = self.0.take(field);
         
         if let Some(mut group) = o_group {
@@ -45,7 +81,7 @@ impl<Field: WholeNumber, Identifier: Eq + Clone + Copy> Catalogue<Field, Identif
         }
     }
 
-    pub fn remove(&mut self, field: Field, scalar: Scalar, meta: Identifier){
+    pub fn remove(&mut self, field: Field, scalar: Scalar, meta: u32){
         let o_group = self.0.take(field);
 
         if let Some(mut group) = o_group {
@@ -55,13 +91,283 @@ impl<Field: WholeNumber, Identifier: Eq + Clone + Copy> Catalogue<Field, Identif
         }
     }
 
-    pub fn get(&self, field: Field, scalar: Scalar) -> Vec<Identifier> {
+    pub fn get(&self, field: Field, scalar: Scalar) -> Posting {
         let o_group = self.0.get(field);
 
         if let Some(group) = o_group {
             group.read(scalar)
         }else {
-            vec![]
+            Posting::new()
+        }
+    }
+
+    // None if the index can't order the given bounds,
+    // caller has to look through the data itself then.
+    pub fn get_range(&self, field: Field, lower: Bound<Scalar>, upper: Bound<Scalar>) -> Option<Posting> {
+        let o_group = self.0.get(field);
+
+        if let Some(group) = o_group {
+            group.read_range(lower, upper)
+        }else {
+            Some(Posting::new())
+        }
+    }
+
+    // Identifiers whose text holds the query words, best match first.
+    // None if the field has no full text index.
+    pub fn search(&self, field: Field, query: &str) -> Option<Vec<u32>> {
+        match self.0.get(field) {
+            Some(group) => group.search(query),
+            None => None
+        }
+    }
+
+    // Tells how the index of field differs from the one in expected,
+    // which is built afresh from the records. None if either lacks it.
+    pub fn compare(&self, field: Field, expected: &Self) -> Option<Difference> {
+        match (self.0.get(field), expected.0.get(field)) {
+            (Some(live), Some(expected)) => Some(live.compare(expected)),
+            _ => None
+        }
+    }
+
+    // Takes the index of field over from the other catalogue
+    pub fn replace(&mut self, field: Field, other: &mut Self) {
+        if let Some(group) = other.0.take(field) {
+            self.0.take(field);
+            self.0.reserve(Some(field));
+            self.0.replace(field, group);
+        }
+    }
+
+    pub fn setup_compound(&mut self, fields: Vec<Field>) {
+        if self.has_compound(&fields) {
+            panic!("Fields are already indexed together")
+        }
+
+        self.1.push(Compound::new(fields))
+    }
+
+    pub fn drop_compound(&mut self, fields: &[Field]) {
+        if !self.has_compound(fields) {
+            panic!("Fields are not indexed together")
+        }
+
+        self.1.retain(|compound| compound.get_fields() != fields)
+    }
+
+    pub fn has_compound(&self, fields: &[Field]) -> bool {
+        self.1.iter().any(|compound| compound.get_fields() == fields)
+    }
+
+    pub fn get_compounds(&self) -> Vec<Vec<Field>> {
+        self.1.iter().map(|compound| compound.get_fields().to_vec()).collect()
+    }
+
+    pub fn compare_compound(&self, fields: &[Field], expected: &Self) -> Option<Difference> {
+        let o_live = self.1.iter().find(|compound| compound.get_fields() == fields);
+        let o_expected = expected.1.iter().find(|compound| compound.get_fields() == fields);
+
+        match (o_live, o_expected) {
+            (Some(live), Some(expected)) => Some(live.compare(expected)),
+            _ => None
+        }
+    }
+
+    pub fn replace_compound(&mut self, fields: &[Field], other: &mut Self) {
+        if let Some(index) = other.1.iter().position(|compound| compound.get_fields() == fields) {
+            let compound = other.1.remove(index);
+
+            self.1.retain(|compound| compound.get_fields() != fields);
+            self.1.push(compound);
+        }
+    }
+
+    // Values are given in the order of compound fields
+    pub fn add_compound(&mut self, fields: &[Field], values: &[Option<Scalar>], meta: u32) {
+        if let Some(compound) = self.1.iter_mut().find(|compound| compound.get_fields() == fields) {
+            compound.add(values, meta)
+        }
+    }
+
+    pub fn remove_compound(&mut self, fields: &[Field], values: &[Option<Scalar>], meta: u32) {
+        if let Some(compound) = self.1.iter_mut().find(|compound| compound.get_fields() == fields) {
+            compound.remove(values, meta)
+        }
+    }
+
+    // Equality on leading fields and a range on the field after them.
+    // May hold identifiers which don't satisfy the range, when its
+    // type differs from the stored value, so they need a check.
+    pub fn get_compound(&self, fields: &[Field], prefix: &[Scalar], lower: Bound<Scalar>, upper: Bound<Scalar>) -> Posting {
+        match self.1.iter().find(|compound| compound.get_fields() == fields) {
+            Some(compound) => compound.read(prefix, lower, upper),
+            None => Posting::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scalar::Decimal;
+    use decimal::{ D8, D32, DecimalTrait };
+
+    fn decimal(s: &str) -> Scalar {
+        Scalar::Decimal(Decimal::from_string(s.to_owned()).unwrap())
+    }
+
+    #[test]
+    fn setup_and_drop() {
+        let mut catalogue: Catalogue<u8> = Catalogue::new();
+        catalogue.setup(2, Kind::Plain);
+        catalogue.add(2, Scalar::Boolean(true), 7);
+
+        assert!(catalogue.has_index(2));
+        assert!(!catalogue.has_index(1));
+
+        catalogue.drop(2);
+
+        assert!(!catalogue.has_index(2));
+        assert!(catalogue.get(2, Scalar::Boolean(true)).is_empty());
+
+        // set up again starts empty
+        catalogue.setup(2, Kind::Plain);
+
+        assert!(catalogue.get(2, Scalar::Boolean(true)).is_empty());
+    }
+
+    #[test]
+    fn unique() {
+        let mut catalogue: Catalogue<u8> = Catalogue::new();
+        catalogue.setup(1, Kind::Unique);
+        catalogue.setup(2, Kind::Plain);
+
+        catalogue.add(1, Scalar::Integer(scalar::Integer::new(5)), 7);
+        catalogue.add(2, Scalar::Integer(scalar::Integer::new(5)), 7);
+
+        assert!(catalogue.conflicts(1, decimal("5.0"), Some(8)));
+        assert!(catalogue.conflicts(1, decimal("5.0"), None));
+        assert!(!catalogue.conflicts(1, decimal("5.0"), Some(7)));
+        assert!(!catalogue.conflicts(1, decimal("5.5"), None));
+        assert!(!catalogue.conflicts(2, decimal("5.0"), Some(8)));
+        assert_eq!(catalogue.get_kind(1), Some(Kind::Unique));
+        assert_eq!(catalogue.get_kind(3), None);
+    }
+
+    #[test]
+    fn full_text() {
+        let mut catalogue: Catalogue<u8> = Catalogue::new();
+        catalogue.setup(1, Kind::FullText(true));
+        catalogue.setup(2, Kind::Plain);
+
+        catalogue.add(1, Scalar::Text("Walking boots".to_owned()), 7);
+        catalogue.add(1, Scalar::Text("Boots for running".to_owned()), 8);
+        catalogue.add(1, Scalar::Integer(scalar::Integer::new(5)), 9);
+
+        assert_eq!(catalogue.search(1, "boot walk"), Some(vec![7]));
+        assert_eq!(catalogue.search(1, "boots").map(|ids| ids.len()), Some(2));
+        assert_eq!(catalogue.search(2, "boots"), None);
+        assert_eq!(catalogue.get(1, Scalar::Integer(scalar::Integer::new(5))).collect(), vec![9]);
+
+        catalogue.remove(1, Scalar::Text("Walking boots".to_owned()), 7);
+
+        assert_eq!(catalogue.search(1, "boots"), Some(vec![8]));
+    }
+
+    #[test]
+    fn compare_and_replace() {
+        let mut live: Catalogue<u8> = Catalogue::new();
+        let mut expected: Catalogue<u8> = Catalogue::new();
+
+        for catalogue in [&mut live, &mut expected] {
+            catalogue.setup(1, Kind::Plain);
+            catalogue.setup(2, Kind::FullText(false));
+            catalogue.setup_compound(vec![1, 2]);
         }
+
+        let five = Scalar::Integer(scalar::Integer::new(5));
+        let text = Scalar::Text("red hat".to_owned());
+
+        expected.add(1, five.clone(), 7);
+        expected.add(1, five.clone(), 8);
+        expected.add(2, text.clone(), 7);
+        expected.add_compound(&[1, 2], &[Some(five.clone()), Some(text.clone())], 7);
+
+        // 8 went missing, 9 stayed behind after going away
+        live.add(1, five.clone(), 7);
+        live.add(1, decimal("5.5"), 9);
+        live.add(2, text.clone(), 7);
+        live.add(2, Scalar::Text("red".to_owned()), 7);
+        live.add_compound(&[1, 2], &[Some(five.clone()), None], 7);
+
+        let (missing, stale) = live.compare(1, &expected).unwrap();
+
+        assert_eq!((missing.collect(), stale.collect()), (vec![8], vec![9]));
+
+        // word counted twice
+        let (missing, stale) = live.compare(2, &expected).unwrap();
+
+        assert_eq!((missing.collect(), stale.collect()), (vec![7], vec![7]));
+
+        let (missing, stale) = live.compare_compound(&[1, 2], &expected).unwrap();
+
+        assert_eq!((missing.collect(), stale.collect()), (vec![7], vec![7]));
+        assert!(live.compare(3, &expected).is_none());
+
+        live.replace(1, &mut expected);
+        live.replace(2, &mut expected);
+        live.replace_compound(&[1, 2], &mut expected);
+
+        assert_eq!(live.get(1, five).collect(), vec![7, 8]);
+        assert_eq!(live.search(2, "hat"), Some(vec![7]));
+        assert_eq!(live.get_compounds(), vec![vec![1, 2]]);
+        assert!(!expected.has_index(1));
+    }
+
+    #[test]
+    fn decimal_widths_collide() {
+        let mut catalogue: Catalogue<u8> = Catalogue::new();
+        catalogue.setup(1, Kind::Plain);
+
+        let tiny = Scalar::Decimal(Decimal::Tiny(D8::from_string("0.5".to_owned()).unwrap()));
+        let medium = Scalar::Decimal(Decimal::Medium(D32::from_string("0.5".to_owned()).unwrap()));
+
+        catalogue.add(1, tiny.clone(), 10);
+        catalogue.add(1, medium.clone(), 20);
+
+        assert_eq!(catalogue.get(1, tiny.clone()), catalogue.get(1, medium.clone()));
+        assert_eq!(catalogue.get(1, medium).len(), 2);
+
+        catalogue.remove(1, tiny.clone(), 10);
+        catalogue.remove(1, tiny.clone(), 20);
+
+        assert!(catalogue.get(1, tiny).is_empty());
+    }
+
+    #[test]
+    fn decimal_range() {
+        let mut catalogue: Catalogue<u8> = Catalogue::new();
+        catalogue.setup(1, Kind::Plain);
+
+        let prices = ["0.5", "1.25", "9.99", "10.0", "10.5", "-3.5", "123456789012345678901234.5"];
+
+        for (id, price) in prices.iter().enumerate() {
+            catalogue.add(1, decimal(price), id as u32);
+        }
+
+        let ids = catalogue.get_range(1, Bound::Included(decimal("1.25")), Bound::Excluded(decimal("10.5"))).unwrap().collect();
+
+        assert_eq!(ids, vec![1, 2, 3]);
+
+        let ids = catalogue.get_range(1, Bound::Excluded(decimal("10.0")), Bound::Unbounded).unwrap().collect();
+
+        assert_eq!(ids, vec![4, 6]);
+
+        // integer bounds are compared on value
+        let ids = catalogue.get_range(1, Bound::Unbounded, Bound::Excluded(Scalar::Integer(scalar::Integer::new(0)))).unwrap().collect();
+
+        assert_eq!(ids, vec![5]);
+        assert!(catalogue.get_range(1, Bound::Unbounded, Bound::Included(Scalar::Text("a".to_owned()))).is_none());
     }
-}
\ No newline at end of file
+}
@@ -1,47 +1,51 @@
-use ds::LinkedList;
+use ds::Posting;
 use std::collections::HashMap;
 use crate::CatalogueTrait;
 
-pub struct Text<T>(HashMap<String, LinkedList<T>>);
+pub struct Text(HashMap<String, Posting>);
 
-impl<T: Eq + Copy> Text<T> {
+impl Text {
     pub fn new() -> Self {
         Self (HashMap::new())
     }
 }
 
-impl<T: Eq + Copy> CatalogueTrait<String, T> for Text<T> {
-    fn add(&mut self, key: String, value: T) {
-        let o_ll = self.0.get_mut(&key);
+impl CatalogueTrait<String> for Text {
+    fn add(&mut self, key: String, value: u32) {
+        let o_posting = self.0.get_mut(&key);
 
-        if o_ll.is_none() {
-            let mut ll = LinkedList::new();
-            ll.add(value);
-            self.0.insert(key.clone(), ll);
-        }else if let Some(ll) = o_ll {
-            ll.add(value);
+        if o_posting.is_none() {
+            let mut posting = Posting::new();
+            posting.add(value);
+            self.0.insert(key.clone(), posting);
+        }else if let Some(posting) = o_posting {
+            posting.add(value);
         }
     }
 
-    fn remove(&mut self, key: String, value: T) {
-        let o_ll = self.0.get_mut(&key);
+    fn remove(&mut self, key: String, value: u32) {
+        let o_posting = self.0.get_mut(&key);
 
-        if let Some(ll) = o_ll {
-            ll.remove(value);
+        if let Some(posting) = o_posting {
+            posting.remove(value);
 
-            if ll.is_empty() {
+            if posting.is_empty() {
                 self.0.remove(&key);
             }
         }
     }
 
-    fn read(&self, key: String) -> Vec<T> {
-        let o_ll = self.0.get(&key);
+    fn read(&self, key: String) -> Posting {
+        let o_posting = self.0.get(&key);
 
-        if let Some(ll) = o_ll {
-            ll.collect()
+        if let Some(posting) = o_posting {
+            posting.clone()
         }else {
-            vec![]
+            Posting::new()
         }
     }
-}
\ No newline at end of file
+
+    fn entries(&self) -> Vec<(String, Posting)> {
+        self.0.iter().map(|(key, posting)| (key.clone(), posting.clone())).collect()
+    }
+}
@@ -0,0 +1,373 @@
+/// DBig is an arbitrary precision decimal for values which
+/// don't fit in any of the fixed D8..D64 shapes.
+/// It keeps decimal digits of the unscaled magnitude, most
+/// significant first, and stores them packed two per byte.
+/// Value is always kept in its shortest form (no leading zeros,
+/// no trailing zeros after point), so derived Eq compares values.
+use std::cmp::Ordering;
+use std::fmt;
+use crate::def::ParseError;
+use crate::spec::Rounding;
+
+const SIGN_BIT: u8 = 0x80;
+pub const MAX_BIG_SCALE: usize = (SIGN_BIT - 1) as usize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DBig {
+    negative: bool,
+    scale: u8,
+    digits: Vec<u8>
+}
+
+impl DBig {
+    fn new(negative: bool, scale: u8, digits: Vec<u8>) -> Self {
+        let mut big = Self {
+            negative,
+            scale,
+            digits
+        };
+
+        big.shorten();
+        big
+    }
+
+    fn shorten(&mut self) {
+        // remove trailing zeros after point
+        while self.scale > 0 && self.digits.last() == Some(&0) {
+            self.digits.pop();
+            self.scale -= 1;
+        }
+
+        // remove leading zeros
+        let leading_zeros = self.digits.iter().take_while(|digit| **digit == 0).count();
+        self.digits.drain(0..leading_zeros);
+
+        if self.digits.is_empty() {
+            self.scale = 0;
+            self.negative = false;
+        }
+    }
+
+    pub fn from_string(s: String) -> Result<Self, ParseError> {
+        let split: Vec<&str> = s.split('.').collect();
+
+        if split.len() != 2 {
+            return Err(ParseError::NotValidDecimal);
+        }
+
+        let mut left = split[0];
+        let right = split[1];
+        let negative = left.starts_with('-');
+
+        if negative {
+            left = &left[1..];
+        }
+
+        if left.is_empty() || right.is_empty() {
+            return Err(ParseError::NotValidNumerics);
+        }else if right.len() > MAX_BIG_SCALE {
+            return Err(ParseError::OutOfBound);
+        }
+
+        let mut digits: Vec<u8> = Vec::with_capacity(left.len() + right.len());
+
+        for byte in left.bytes().chain(right.bytes()) {
+            if !byte.is_ascii_digit() {
+                return Err(ParseError::NotValidNumerics);
+            }
+
+            digits.push(byte - b'0');
+        }
+
+        Ok(Self::new(negative, right.len() as u8, digits))
+    }
+
+    fn write_digits(&self) -> String {
+        let scale = self.scale as usize;
+        let mut s: String = self.digits.iter().map(|digit| (digit + b'0') as char).collect();
+
+        if scale == 0 {
+            if s.is_empty() {
+                s.push('0');
+            }
+
+            s.push_str(".0");
+        }else if s.len() > scale {
+            s.insert(s.len() - scale, '.');
+        }else {
+            let zeros = "0".repeat(scale - s.len());
+            s.insert_str(0, &zeros);
+            s.insert_str(0, "0.");
+        }
+
+        if self.negative {
+            s.insert(0, '-');
+        }
+
+        s
+    }
+
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = Vec::with_capacity(1 + self.digits.len().div_ceil(2));
+        let mut head = self.scale;
+
+        if self.negative {
+            head |= SIGN_BIT;
+        }
+
+        bytes.push(head);
+
+        // pad a leading zero so digits can be paired
+        let mut o_high: Option<u8> = if self.digits.len() % 2 == 1 { Some(0) } else { None };
+
+        for digit in &self.digits {
+            match o_high {
+                None => o_high = Some(*digit),
+                Some(high) => {
+                    bytes.push((high << 4) | digit);
+                    o_high = None;
+                }
+            }
+        }
+
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
+        if bytes.is_empty() {
+            return Err(ParseError::NotValidDecimal);
+        }
+
+        let negative = bytes[0] & SIGN_BIT == SIGN_BIT;
+        let scale = bytes[0] & !SIGN_BIT;
+        let mut digits: Vec<u8> = Vec::with_capacity((bytes.len() - 1) * 2);
+
+        for byte in &bytes[1..] {
+            let high = byte >> 4;
+            let low = byte & 0x0F;
+
+            if high > 9 || low > 9 {
+                return Err(ParseError::NotValidNumerics);
+            }
+
+            digits.push(high);
+            digits.push(low);
+        }
+
+        let big = Self::new(negative, scale, digits);
+
+        // Only the shortest form is ever written. Anything
+        // else can only come from corrupted bytes.
+        if big.as_bytes() != bytes {
+            Err(ParseError::NotValidDecimal)
+        }else {
+            Ok(big)
+        }
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn get_scale(&self) -> usize {
+        self.scale as usize
+    }
+
+    // Count of digits before the decimal point
+    pub fn get_integer_count(&self) -> usize {
+        self.digits.len().saturating_sub(self.scale as usize)
+    }
+
+    // Unscaled value at the given scale, if it can be held in i128
+    // without losing any digit.
+    pub fn to_scaled(&self, scale: usize) -> Option<i128> {
+        let own_scale = self.scale as usize;
+
+        if own_scale > scale {
+            return None;
+        }
+
+        let mut value: i128 = 0;
+        let padding = std::iter::repeat_n(&0, scale - own_scale);
+
+        for digit in self.digits.iter().chain(padding) {
+            let digit = *digit as i128;
+
+            value = if self.negative {
+                value.checked_mul(10)?.checked_sub(digit)?
+            }else {
+                value.checked_mul(10)?.checked_add(digit)?
+            };
+        }
+
+        Some(value)
+    }
+
+    pub fn from_scaled(value: i128, scale: usize) -> Self {
+        let digits: Vec<u8> = value.unsigned_abs().to_string().bytes().map(|byte| byte - b'0').collect();
+
+        Self::new(value < 0, scale as u8, digits)
+    }
+
+    pub fn round(&self, scale: usize, rounding: Rounding) -> Self {
+        let own_scale = self.scale as usize;
+
+        if own_scale <= scale {
+            return self.clone();
+        }
+
+        let drop = own_scale - scale;
+        let mut padded: Vec<u8> = vec![0; (drop + 1).saturating_sub(self.digits.len())];
+        padded.extend(&self.digits);
+
+        let split = padded.len() - drop;
+        let mut kept = padded[0..split].to_vec();
+        let first_dropped = padded[split];
+        let rest_is_zero = padded[(split + 1)..].iter().all(|digit| *digit == 0);
+        let last_kept_is_odd = kept.last().map(|digit| digit % 2 == 1).unwrap_or(false);
+
+        let increment = match rounding {
+            Rounding::Truncate => false,
+            Rounding::HalfUp => first_dropped >= 5,
+            Rounding::HalfEven => first_dropped > 5 || (first_dropped == 5 && (!rest_is_zero || last_kept_is_odd))
+        };
+
+        if increment {
+            let mut index = kept.len();
+
+            loop {
+                if index == 0 {
+                    kept.insert(0, 1);
+                    break;
+                }
+
+                index -= 1;
+
+                if kept[index] == 9 {
+                    kept[index] = 0;
+                }else {
+                    kept[index] += 1;
+                    break;
+                }
+            }
+        }
+
+        Self::new(self.negative, scale as u8, kept)
+    }
+
+    fn cmp_magnitude(&self, other: &Self) -> Ordering {
+        let integer_count = self.get_integer_count();
+        let other_integer_count = other.get_integer_count();
+
+        if integer_count != other_integer_count {
+            return integer_count.cmp(&other_integer_count);
+        }
+
+        // same count before point, so align the digits at point
+        let scale = std::cmp::max(self.scale, other.scale) as usize;
+        let padded = |big: &Self| -> Vec<u8> {
+            let mut digits: Vec<u8> = vec![0; (integer_count + big.scale as usize).saturating_sub(big.digits.len())];
+            digits.extend(&big.digits);
+            digits.resize(integer_count + scale, 0);
+            digits
+        };
+
+        padded(self).cmp(&padded(other))
+    }
+}
+
+impl fmt::Display for DBig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.write_digits())
+    }
+}
+
+impl PartialOrd for DBig {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DBig {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => self.cmp_magnitude(other),
+            (true, true) => other.cmp_magnitude(self)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(s: &str) -> DBig {
+        DBig::from_string(s.to_owned()).unwrap()
+    }
+
+    #[test]
+    fn string() {
+        for s in ["0.0", "0.5", "-0.005", "12345678901234567890.123", "-100.0", "7.25"] {
+            assert_eq!(big(s).to_string(), s);
+        }
+
+        assert_eq!(big("007.500").to_string(), "7.5");
+        assert_eq!(big("-0.000").to_string(), "0.0");
+        assert!(DBig::from_string("1".to_owned()).is_err());
+        assert!(DBig::from_string("1.".to_owned()).is_err());
+        assert!(DBig::from_string("1.2a".to_owned()).is_err());
+    }
+
+    #[test]
+    fn bytes() {
+        for s in ["0.0", "1.5", "-0.005", "12345678901234567890.123"] {
+            let value = big(s);
+
+            assert_eq!(DBig::from_bytes(&value.as_bytes()).unwrap(), value);
+        }
+
+        assert!(DBig::from_bytes(&[]).is_err());
+        assert!(DBig::from_bytes(&[1, 0x10]).is_err());
+        assert!(DBig::from_bytes(&[0, 0x00, 0x01]).is_err());
+        assert!(DBig::from_bytes(&[0, 0xA1]).is_err());
+    }
+
+    #[test]
+    fn order() {
+        assert!(big("10.5") > big("10.0"));
+        assert!(big("9.99") < big("10.0"));
+        assert!(big("-10.5") < big("-10.0"));
+        assert!(big("-0.1") < big("0.0"));
+        assert!(big("0.01") < big("0.1"));
+        assert_eq!(big("2.50").cmp(&big("2.5")), Ordering::Equal);
+    }
+
+    #[test]
+    fn scaled() {
+        assert_eq!(big("-1.25").to_scaled(2), Some(-125));
+        assert_eq!(big("1.25").to_scaled(1), None);
+        assert_eq!(DBig::from_scaled(-125, 2), big("-1.25"));
+    }
+
+    #[test]
+    fn round() {
+        let cases = [
+            ("2.345", 2, Rounding::HalfEven, "2.34"),
+            ("2.355", 2, Rounding::HalfEven, "2.36"),
+            ("2.3451", 2, Rounding::HalfEven, "2.35"),
+            ("2.345", 2, Rounding::HalfUp, "2.35"),
+            ("2.349", 2, Rounding::Truncate, "2.34"),
+            ("-2.345", 2, Rounding::HalfUp, "-2.35"),
+            ("9.99", 1, Rounding::HalfUp, "10.0"),
+            ("0.005", 2, Rounding::HalfUp, "0.01"),
+            ("0.004", 2, Rounding::HalfUp, "0.0"),
+            ("12345.678", 0, Rounding::HalfEven, "12346.0")
+        ];
+
+        for (value, scale, rounding, expected) in cases {
+            assert_eq!(big(value).round(scale, rounding).to_string(), expected);
+        }
+    }
+}
@@ -4,7 +4,8 @@ pub const MAX_SCALE: usize = 10;
 pub enum ParseError {
     NotValidDecimal,
     OutOfBound,
-    NotValidNumerics
+    NotValidNumerics,
+    NotValidSpec
 }
 
 pub trait DecimalTrait<T, const N: usize> 
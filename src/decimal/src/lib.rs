@@ -4,8 +4,12 @@ mod d8;
 mod d16;
 mod d32;
 mod d64;
+mod dbig;
+mod spec;
 pub use d8::D8;
 pub use d16::D16;
 pub use d32::D32;
 pub use d64::D64;
+pub use dbig::DBig;
+pub use spec::{ Spec, Rounding };
 pub use def::{ DecimalTrait, ParseError, MAX_SCALE };
\ No newline at end of file
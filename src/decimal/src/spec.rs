@@ -0,0 +1,135 @@
+/// Spec is a declared shape of a decimal field.
+/// Precision is the total count of digits and scale is the
+/// count of digits after the decimal point. Any value given
+/// for the field is rounded to scale and rejected when its
+/// digits before point exceed precision - scale.
+use crate::def::ParseError;
+use crate::dbig::{ DBig, MAX_BIG_SCALE };
+
+const HALF_EVEN: u8 = 0;
+const HALF_UP: u8 = 1;
+const TRUNCATE: u8 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rounding {
+    HalfEven,
+    HalfUp,
+    Truncate
+}
+
+impl Rounding {
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            b"half_even" => Some(Self::HalfEven),
+            b"half_up" => Some(Self::HalfUp),
+            b"truncate" => Some(Self::Truncate),
+            _ => None
+        }
+    }
+
+    fn get_value(&self) -> u8 {
+        match self {
+            Self::HalfEven => HALF_EVEN,
+            Self::HalfUp => HALF_UP,
+            Self::Truncate => TRUNCATE
+        }
+    }
+
+    fn get_type(value: u8) -> Option<Self> {
+        match value {
+            HALF_EVEN => Some(Self::HalfEven),
+            HALF_UP => Some(Self::HalfUp),
+            TRUNCATE => Some(Self::Truncate),
+            _ => None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Spec {
+    precision: u8,
+    scale: u8,
+    rounding: Rounding
+}
+
+impl Spec {
+    pub fn new(precision: u8, scale: u8, rounding: Rounding) -> Result<Self, ParseError> {
+        if precision == 0 || scale > precision || scale as usize > MAX_BIG_SCALE {
+            Err(ParseError::NotValidSpec)
+        }else {
+            Ok(Self {
+                precision,
+                scale,
+                rounding
+            })
+        }
+    }
+
+    pub fn get_precision(&self) -> u8 {
+        self.precision
+    }
+
+    pub fn get_scale(&self) -> u8 {
+        self.scale
+    }
+
+    pub fn get_rounding(&self) -> Rounding {
+        self.rounding
+    }
+
+    pub fn apply(&self, value: &DBig) -> Result<DBig, ParseError> {
+        let rounded = value.round(self.scale as usize, self.rounding);
+
+        if rounded.get_integer_count() > (self.precision - self.scale) as usize {
+            Err(ParseError::OutOfBound)
+        }else {
+            Ok(rounded)
+        }
+    }
+
+    pub fn as_bytes(&self) -> [u8; 3] {
+        [self.precision, self.scale, self.rounding.get_value()]
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
+        if bytes.len() != 3 {
+            return Err(ParseError::NotValidSpec);
+        }
+
+        match Rounding::get_type(bytes[2]) {
+            None => Err(ParseError::NotValidSpec),
+            Some(rounding) => Self::new(bytes[0], bytes[1], rounding)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply() {
+        let spec = Spec::new(7, 2, Rounding::HalfEven).unwrap();
+        let value = DBig::from_string("12345.678".to_owned()).unwrap();
+
+        assert_eq!(spec.apply(&value).unwrap().to_string(), "12345.68");
+
+        let value = DBig::from_string("123456.1".to_owned()).unwrap();
+
+        assert!(spec.apply(&value).is_err());
+
+        // rounding may carry into one more digit before point
+        let value = DBig::from_string("99999.999".to_owned()).unwrap();
+
+        assert!(spec.apply(&value).is_err());
+    }
+
+    #[test]
+    fn bytes() {
+        let spec = Spec::new(10, 3, Rounding::Truncate).unwrap();
+
+        assert_eq!(Spec::from_bytes(&spec.as_bytes()).unwrap(), spec);
+        assert!(Spec::from_bytes(&[2, 3, 0]).is_err());
+        assert!(Spec::from_bytes(&[2, 1, 9]).is_err());
+    }
+}
@@ -2,10 +2,13 @@ mod stack;
 mod linked_list;
 mod avl;
 mod btree;
+mod posting;
+pub mod varint;
 pub use stack::Stack;
 pub use linked_list::LinkedList;
 pub use avl::AVL;
 pub use avl::RationalNumber;
 pub use btree::BTree;
+pub use posting::Posting;
 
 
@@ -0,0 +1,456 @@
+//! Posting is a sorted set of u32 kept compact.
+//! Values are split by their high 16 bits into containers. A container
+//! holds the low 16 bits as a sorted array while it is small, and as a
+//! bitmap of all 65536 values once an array would take more room.
+
+// An array of this many u16 takes as much as a bitmap
+const ARRAY_MAX: usize = 4096;
+const BITMAP_WORDS: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Container {
+    Array(Vec<u16>),
+    // words and count of set bits
+    Bitmap(Box<[u64; BITMAP_WORDS]>, usize)
+}
+
+impl Container {
+    fn len(&self) -> usize {
+        match self {
+            Self::Array(values) => values.len(),
+            Self::Bitmap(_, count) => *count
+        }
+    }
+
+    fn contains(&self, low: u16) -> bool {
+        match self {
+            Self::Array(values) => values.binary_search(&low).is_ok(),
+            Self::Bitmap(words, _) => words[low as usize / 64] & (1 << (low % 64)) != 0
+        }
+    }
+
+    fn add(&mut self, low: u16) {
+        match self {
+            Self::Array(values) => {
+                if let Err(index) = values.binary_search(&low) {
+                    values.insert(index, low);
+
+                    if values.len() > ARRAY_MAX {
+                        *self = Self::to_bitmap(values);
+                    }
+                }
+            },
+            Self::Bitmap(words, count) => {
+                let word = &mut words[low as usize / 64];
+                let bit = 1 << (low % 64);
+
+                if *word & bit == 0 {
+                    *word |= bit;
+                    *count += 1;
+                }
+            }
+        }
+    }
+
+    fn remove(&mut self, low: u16) {
+        match self {
+            Self::Array(values) => {
+                if let Ok(index) = values.binary_search(&low) {
+                    values.remove(index);
+                }
+            },
+            Self::Bitmap(words, count) => {
+                let word = &mut words[low as usize / 64];
+                let bit = 1 << (low % 64);
+
+                if *word & bit != 0 {
+                    *word &= !bit;
+                    *count -= 1;
+
+                    if *count <= ARRAY_MAX {
+                        *self = Self::Array(Self::bits(words));
+                    }
+                }
+            }
+        }
+    }
+
+    fn to_bitmap(values: &[u16]) -> Self {
+        let mut words = Box::new([0u64; BITMAP_WORDS]);
+
+        for low in values {
+            words[*low as usize / 64] |= 1 << (low % 64);
+        }
+
+        Self::Bitmap(words, values.len())
+    }
+
+    // Smaller of the two forms for given words
+    fn from_words(words: Box<[u64; BITMAP_WORDS]>) -> Self {
+        let count = words.iter().map(|word| word.count_ones() as usize).sum();
+
+        if count <= ARRAY_MAX {
+            Self::Array(Self::bits(&words))
+        }else {
+            Self::Bitmap(words, count)
+        }
+    }
+
+    fn from_array(values: Vec<u16>) -> Self {
+        if values.len() > ARRAY_MAX {
+            Self::to_bitmap(&values)
+        }else {
+            Self::Array(values)
+        }
+    }
+
+    fn bits(words: &[u64; BITMAP_WORDS]) -> Vec<u16> {
+        let mut values: Vec<u16> = Vec::new();
+
+        for (index, word) in words.iter().enumerate() {
+            let mut word = *word;
+
+            while word != 0 {
+                let bit = word.trailing_zeros();
+                values.push((index * 64) as u16 + bit as u16);
+                word &= word - 1;
+            }
+        }
+
+        values
+    }
+
+    fn collect(&self) -> Vec<u16> {
+        match self {
+            Self::Array(values) => values.clone(),
+            Self::Bitmap(words, _) => Self::bits(words)
+        }
+    }
+
+    fn intersect(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Self::Array(a), Self::Array(b)) => {
+                let (small, large) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+
+                // a much smaller side is cheaper to look up than to walk
+                if small.len() * 16 < large.len() {
+                    Self::Array(small.iter().copied().filter(|low| large.binary_search(low).is_ok()).collect())
+                }else {
+                    let mut values: Vec<u16> = Vec::new();
+                    let (mut i, mut j) = (0, 0);
+
+                    while i < a.len() && j < b.len() {
+                        if a[i] < b[j] {
+                            i += 1;
+                        }else if a[i] > b[j] {
+                            j += 1;
+                        }else {
+                            values.push(a[i]);
+                            i += 1;
+                            j += 1;
+                        }
+                    }
+
+                    Self::Array(values)
+                }
+            },
+            (Self::Array(values), bitmap @ Self::Bitmap(_, _)) | (bitmap @ Self::Bitmap(_, _), Self::Array(values)) => {
+                Self::Array(values.iter().copied().filter(|low| bitmap.contains(*low)).collect())
+            },
+            (Self::Bitmap(a, _), Self::Bitmap(b, _)) => {
+                let mut words = Box::new([0u64; BITMAP_WORDS]);
+
+                for index in 0..BITMAP_WORDS {
+                    words[index] = a[index] & b[index];
+                }
+
+                Self::from_words(words)
+            }
+        }
+    }
+
+    fn union(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Self::Array(a), Self::Array(b)) => {
+                let mut values: Vec<u16> = Vec::with_capacity(a.len() + b.len());
+                let (mut i, mut j) = (0, 0);
+
+                while i < a.len() && j < b.len() {
+                    if a[i] < b[j] {
+                        values.push(a[i]);
+                        i += 1;
+                    }else if a[i] > b[j] {
+                        values.push(b[j]);
+                        j += 1;
+                    }else {
+                        values.push(a[i]);
+                        i += 1;
+                        j += 1;
+                    }
+                }
+
+                values.extend(&a[i..]);
+                values.extend(&b[j..]);
+
+                Self::from_array(values)
+            },
+            (Self::Array(values), Self::Bitmap(words, _)) | (Self::Bitmap(words, _), Self::Array(values)) => {
+                let mut words = words.clone();
+
+                for low in values {
+                    words[*low as usize / 64] |= 1 << (low % 64);
+                }
+
+                Self::from_words(words)
+            },
+            (Self::Bitmap(a, _), Self::Bitmap(b, _)) => {
+                let mut words = Box::new([0u64; BITMAP_WORDS]);
+
+                for index in 0..BITMAP_WORDS {
+                    words[index] = a[index] | b[index];
+                }
+
+                Self::from_words(words)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Posting {
+    // high 16 bits of values, sorted
+    keys: Vec<u16>,
+    containers: Vec<Container>
+}
+
+impl Posting {
+    pub fn new() -> Self {
+        Self {
+            keys: Vec::new(),
+            containers: Vec::new()
+        }
+    }
+
+    // Cheaper than adding one by one or a union
+    // per source when values come from many postings.
+    pub fn from_vec(mut values: Vec<u32>) -> Self {
+        let mut posting = Self::new();
+
+        values.sort_unstable();
+        values.dedup();
+
+        for chunk in values.chunk_by(|a, b| a >> 16 == b >> 16) {
+            posting.keys.push((chunk[0] >> 16) as u16);
+            posting.containers.push(Container::from_array(chunk.iter().map(|value| *value as u16).collect()));
+        }
+
+        posting
+    }
+
+    fn split(value: u32) -> (u16, u16) {
+        ((value >> 16) as u16, value as u16)
+    }
+
+    pub fn add(&mut self, value: u32) {
+        let (high, low) = Self::split(value);
+
+        match self.keys.binary_search(&high) {
+            Ok(index) => self.containers[index].add(low),
+            Err(index) => {
+                self.keys.insert(index, high);
+                self.containers.insert(index, Container::Array(vec![low]));
+            }
+        }
+    }
+
+    pub fn remove(&mut self, value: u32) {
+        let (high, low) = Self::split(value);
+
+        if let Ok(index) = self.keys.binary_search(&high) {
+            self.containers[index].remove(low);
+
+            if self.containers[index].len() == 0 {
+                self.keys.remove(index);
+                self.containers.remove(index);
+            }
+        }
+    }
+
+    pub fn contains(&self, value: u32) -> bool {
+        let (high, low) = Self::split(value);
+
+        match self.keys.binary_search(&high) {
+            Ok(index) => self.containers[index].contains(low),
+            Err(_) => false
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.containers.iter().map(|container| container.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    // Values in ascending order
+    pub fn collect(&self) -> Vec<u32> {
+        let mut result: Vec<u32> = Vec::with_capacity(self.len());
+
+        for (high, container) in self.keys.iter().zip(&self.containers) {
+            let high = (*high as u32) << 16;
+
+            result.extend(container.collect().into_iter().map(|low| high | low as u32));
+        }
+
+        result
+    }
+
+    pub fn intersect(&self, other: &Self) -> Self {
+        let mut result = Self::new();
+        let (mut i, mut j) = (0, 0);
+
+        while i < self.keys.len() && j < other.keys.len() {
+            if self.keys[i] < other.keys[j] {
+                i += 1;
+            }else if self.keys[i] > other.keys[j] {
+                j += 1;
+            }else {
+                let container = self.containers[i].intersect(&other.containers[j]);
+
+                if container.len() > 0 {
+                    result.keys.push(self.keys[i]);
+                    result.containers.push(container);
+                }
+
+                i += 1;
+                j += 1;
+            }
+        }
+
+        result
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut result = Self::new();
+        let (mut i, mut j) = (0, 0);
+
+        while i < self.keys.len() || j < other.keys.len() {
+            if j == other.keys.len() || (i < self.keys.len() && self.keys[i] < other.keys[j]) {
+                result.keys.push(self.keys[i]);
+                result.containers.push(self.containers[i].clone());
+                i += 1;
+            }else if i == self.keys.len() || self.keys[i] > other.keys[j] {
+                result.keys.push(other.keys[j]);
+                result.containers.push(other.containers[j].clone());
+                j += 1;
+            }else {
+                result.keys.push(self.keys[i]);
+                result.containers.push(self.containers[i].union(&other.containers[j]));
+                i += 1;
+                j += 1;
+            }
+        }
+
+        result
+    }
+
+    // Values here which are not in other
+    pub fn difference(&self, other: &Self) -> Self {
+        let values: Vec<u32> = self.collect().into_iter().filter(|value| !other.contains(*value)).collect();
+
+        Self::from_vec(values)
+    }
+}
+
+impl Default for Posting {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+    use rand::Rng;
+
+    fn random(max: u32, count: usize) -> (Posting, BTreeSet<u32>) {
+        let mut posting = Posting::new();
+        let mut set: BTreeSet<u32> = BTreeSet::new();
+
+        for _ in 0..count {
+            let value = rand::thread_rng().gen_range(0..max);
+
+            posting.add(value);
+            set.insert(value);
+        }
+
+        (posting, set)
+    }
+
+    #[test]
+    fn add_and_remove() {
+        // dense enough in a few containers to turn into bitmaps
+        let (mut posting, mut set) = random(200_000, 20_000);
+
+        assert!(posting.containers.iter().any(|container| matches!(container, Container::Bitmap(_, _))));
+        assert_eq!(posting.collect(), set.iter().copied().collect::<Vec<u32>>());
+        assert_eq!(posting.len(), set.len());
+
+        let values: Vec<u32> = set.iter().copied().collect();
+
+        for value in values.iter().step_by(2) {
+            posting.remove(*value);
+            set.remove(value);
+        }
+
+        // removing what is not there is harmless
+        posting.remove(u32::MAX);
+
+        assert_eq!(posting.collect(), set.iter().copied().collect::<Vec<u32>>());
+        assert!(posting.containers.iter().all(|container| matches!(container, Container::Array(_))));
+
+        for value in set.iter() {
+            assert!(posting.contains(*value));
+            posting.remove(*value);
+        }
+
+        assert!(posting.is_empty());
+    }
+
+    #[test]
+    fn intersect_and_union() {
+        for (max, count) in [(1_000, 300), (500_000, 20_000), (70_000, 60_000)] {
+            let (a, a_set) = random(max, count);
+            let (b, b_set) = random(max, count / 3);
+
+            assert_eq!(a.intersect(&b).collect(), a_set.intersection(&b_set).copied().collect::<Vec<u32>>());
+            assert_eq!(b.intersect(&a).collect(), a_set.intersection(&b_set).copied().collect::<Vec<u32>>());
+            assert_eq!(a.union(&b).collect(), a_set.union(&b_set).copied().collect::<Vec<u32>>());
+            assert_eq!(b.union(&a).len(), a_set.union(&b_set).count());
+            assert_eq!(a.difference(&b).collect(), a_set.difference(&b_set).copied().collect::<Vec<u32>>());
+        }
+
+        let (a, a_set) = random(300_000, 50_000);
+        let (b, b_set) = random(300_000, 2_000);
+        let mut values = a.collect();
+        values.extend(b.collect());
+
+        assert_eq!(Posting::from_vec(values), a.union(&b));
+        assert_eq!(a.union(&b).len(), a_set.union(&b_set).count());
+        assert!(Posting::new().intersect(&random(100, 10).0).is_empty());
+        assert_eq!(Posting::new().union(&Posting::new()), Posting::new());
+    }
+
+    #[test]
+    fn extremes() {
+        let mut posting = Posting::new();
+
+        for value in [u32::MAX, 0, 65_535, 65_536] {
+            posting.add(value);
+        }
+
+        assert_eq!(posting.collect(), vec![0, 65_535, 65_536, u32::MAX]);
+    }
+}
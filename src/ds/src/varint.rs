@@ -0,0 +1,84 @@
+//! Varint writes an unsigned number in as few bytes as it needs.
+//! Each byte holds 7 bits, lowest first, and its high bit tells
+//! another byte follows. Below 128 it is the number as one byte.
+
+const MORE: u8 = 0x80;
+const BITS: u8 = 0x7F;
+// 32 bits never take more than this
+const MAX_LEN: usize = 5;
+
+pub fn push(bytes: &mut Vec<u8>, value: u32) {
+    let mut rest = value;
+
+    while rest >= MORE as u32 {
+        bytes.push((rest as u8 & BITS) | MORE);
+        rest >>= 7;
+    }
+
+    bytes.push(rest as u8);
+}
+
+pub fn encode(value: u32) -> Vec<u8> {
+    let mut bytes: Vec<u8> = Vec::new();
+
+    push(&mut bytes, value);
+
+    bytes
+}
+
+// Reads from pos and moves it past the number. None when the bytes
+// end early or the number does not fit in 32 bits.
+pub fn read(bytes: &[u8], pos: &mut usize) -> Option<u32> {
+    let mut value: u64 = 0;
+
+    for len in 0..MAX_LEN {
+        let byte = *bytes.get(*pos + len)?;
+
+        value |= ((byte & BITS) as u64) << (7 * len);
+
+        if byte & MORE == 0 {
+            // a longer way of writing the same number is refused
+            if len > 0 && byte == 0 {
+                return None;
+            }
+
+            *pos += len + 1;
+
+            return u32::try_from(value).ok();
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codec() {
+        for value in [0, 1, 127, 128, 255, 300, 16_383, 16_384, u16::MAX as u32, u32::MAX] {
+            let bytes = encode(value);
+            let mut pos = 0;
+
+            assert_eq!(read(&bytes, &mut pos), Some(value));
+            assert_eq!(pos, bytes.len());
+        }
+
+        assert_eq!(encode(5), vec![5]);
+        assert_eq!(encode(300), vec![0xAC, 0x02]);
+
+        // read goes on from where the last one stopped
+        let mut bytes = encode(300);
+        push(&mut bytes, 7);
+        let mut pos = 0;
+
+        assert_eq!(read(&bytes, &mut pos), Some(300));
+        assert_eq!(read(&bytes, &mut pos), Some(7));
+        assert_eq!(read(&bytes, &mut pos), None);
+
+        assert_eq!(read(&[0x80], &mut 0), None);
+        assert_eq!(read(&[0x80, 0x00], &mut 0), None);
+        assert_eq!(read(&[0xFF, 0xFF, 0xFF, 0xFF, 0x1F], &mut 0), None);
+    }
+}
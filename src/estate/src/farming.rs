@@ -1,10 +1,12 @@
-
-/// Farming estate is not constant in
-/// size. So a token will be returned
-/// whenever it is alloted and a
-/// token is required to retain
-
-use std::collections::HashMap;
+//! Farming estate is not constant in
+//! size. So a token will be returned
+//! whenever it is alloted and a
+//! token is required to retain.
+//! Allotment is best fit: the smallest retained estate
+//! which is large enough is split, and the rest stays
+//! retained. Neighbouring retained estates are merged.
+
+use std::collections::{BTreeMap, BTreeSet};
 use ds::Stack;
 use crate::def::WholeNumber;
 
@@ -14,164 +16,191 @@ use crate::def::WholeNumber;
 type Location = usize;
 type Size = usize;
 
-#[derive(Clone, Copy)]
-enum State {
-    Occupied(Location),
-    Retained(Location)
-}
-
 pub struct Farming <Address> {
     max: usize,
-    locations: Vec<State>,
-    retained: HashMap<Size, Stack<Address>>
+    // where the land in use ends
+    end: Location,
+    // estate of every address, None once retained
+    estates: Vec<Option<(Location, Size)>>,
+    // retained addresses, handed out again before new ones
+    vacant: Stack<Address>,
+    occupied: BTreeMap<Location, Address>,
+    // retained estates by location to merge them, and by size to fit
+    retained: BTreeMap<Location, Size>,
+    fits: BTreeSet<(Size, Location)>,
+    // blocks in all retained estates together
+    retained_total: Size
 }
 
 impl<Address: WholeNumber> Farming<Address> {
     pub fn new() -> Self {
         Self {
             max: Address::get_max().to_usize(),
-            locations: vec![State::Retained(Address::default().to_usize())],
-            retained: HashMap::new()
+            end: Address::default().to_usize(),
+            estates: Vec::new(),
+            vacant: Stack::new(),
+            occupied: BTreeMap::new(),
+            retained: BTreeMap::new(),
+            fits: BTreeSet::new(),
+            retained_total: 0
         }
     }
 
     pub fn allot(&mut self, size: Size) -> Address {
-        let len = self.locations.len();
-
         if size == 0 {
             panic!("Estate allot size can never be zero")
-        }else if let State::Retained(last) = self.locations[len - 1] {
-            if last + size > self.max {
+        }
+
+        if let Some(address) = self.allot_retained_below(size, self.end) {
+            address
+        }else {
+            if self.end + size > self.max {
                 panic!("Farming overflow")
             }
+
+            let location = self.end;
+
+            self.end += size;
+            self.occupy(location, size)
         }
+    }
 
-        let some_retained = self.retained.get_mut(&size);
+    // Allots the best fitting retained estate lying before location.
+    // None when there is none, the land is never extended for it.
+    pub fn allot_retained_below(&mut self, size: Size, location: Location) -> Option<Address> {
+        let (fit_size, fit_location) = *self.fits.range((size, 0)..).find(|(_, fit_location)| *fit_location < location)?;
 
-        if let Some(retained) = some_retained {
-            let address = retained.take().unwrap();
-            let address_as_usize = address.to_usize();
+        self.take_retained(fit_location, fit_size);
 
-            // delete if size is empty otherwise above unwrap will result in panic
-            if retained.is_empty() {
-                self.retained.remove(&size);
-            }
+        // rest of it stays retained
+        if fit_size > size {
+            self.keep_retained(fit_location + size, fit_size - size);
+        }
 
-            let state = self.locations[address_as_usize];
+        Some(self.occupy(fit_location, size))
+    }
 
-            match state {
-                State::Retained(location) => {
-                    self.locations[address_as_usize] = State::Occupied(location)
-                },
-                _ => panic!("Location showing occupied but it should be retained")
+    fn occupy(&mut self, location: Location, size: Size) -> Address {
+        let address = match self.vacant.take() {
+            Some(address) => {
+                self.estates[address.to_usize()] = Some((location, size));
+                address
+            },
+            None => {
+                self.estates.push(Some((location, size)));
+                Address::from_usize(self.estates.len() - 1)
             }
-            
-            address
-        }else {
-            let s_location = self.locations[len - 1];
-            
-            if let State::Retained(location) = s_location {
-                self.locations[len - 1] = State::Occupied(location);
-                self.locations.push(State::Retained(location + size));
-                
-                Address::from_usize(len - 1)
-            }else {
-                panic!("If this happen, then stop being a coder please!")
+        };
+
+        self.occupied.insert(location, address);
+
+        address
+    }
+
+    fn keep_retained(&mut self, location: Location, size: Size) {
+        self.retained.insert(location, size);
+        self.fits.insert((size, location));
+        self.retained_total += size;
+    }
+
+    fn take_retained(&mut self, location: Location, size: Size) {
+        self.retained.remove(&location);
+        self.fits.remove(&(size, location));
+        self.retained_total -= size;
+    }
+
+    // Size of the retained estate starting at location, if one does
+    pub fn get_retained_size(&self, location: Location) -> Option<Size> {
+        self.retained.get(&location).copied()
+    }
+
+    // Blocks in every retained estate, those at the end of the land too
+    pub fn get_retained_total(&self) -> Size {
+        self.retained_total
+    }
+
+    // Location where the land in use ends
+    pub fn get_end(&self) -> Location {
+        self.end
+    }
+
+    // Occupied estate lying last, if any
+    pub fn get_last(&self) -> Option<Address> {
+        self.occupied.values().next_back().copied()
+    }
+
+    // Occupied estate lying last before location, if any
+    pub fn get_before(&self, location: Location) -> Option<Address> {
+        self.occupied.range(..location).next_back().map(|(_, address)| *address)
+    }
+
+    // Gives back the retained estate lying at the end of the land, so it
+    // ends with the last occupied one. Returns where the land ends now.
+    pub fn trim(&mut self) -> Location {
+        if let Some((&location, &size)) = self.retained.last_key_value() {
+            if location + size == self.end {
+                self.take_retained(location, size);
+                self.end = location;
             }
         }
+
+        self.end
     }
 
     pub fn retain(&mut self, address: Address) {
-        let len = self.locations.len();
         let address_as_usize = address.to_usize();
 
-        if address_as_usize >= len - 1 {
+        if address_as_usize >= self.estates.len() {
             panic!("Address is wrong")
         }
-        // Note: The below code is removed as it was interfering
-        // with register function. Very sad to comment the
-        // below code
-        // else if address_as_usize == len - 2 {
-        //     self.locations.pop();
-        // }
-        else {   
-            let state = self.locations[address_as_usize];
-
-            match state {
-                State::Retained(_) => panic!("Address is already retained"),
-                State::Occupied(location) => {
-                    let size = self.get_size(address).unwrap();
-                    let has_size = self.retained.contains_key(&size);
-
-                    if has_size {
-                        self.retained.get_mut(&size).unwrap().add(address);
-                    }else {
-                        let mut stack = Stack::new();
-                        stack.add(address);
-                        self.retained.insert(size, stack);
-                    }
-
-                    self.locations[address_as_usize] = State::Retained(location);
-                }
+
+        let (mut location, mut size) = match self.estates[address_as_usize].take() {
+            Some(estate) => estate,
+            None => panic!("Address is already retained")
+        };
+
+        self.occupied.remove(&location);
+        self.vacant.add(address);
+
+        // merge with the retained estate right before
+        if let Some((&before, &before_size)) = self.retained.range(..location).next_back() {
+            if before + before_size == location {
+                self.take_retained(before, before_size);
+                location = before;
+                size += before_size;
             }
         }
-    }
 
-    pub fn get_location(&self, address: Address) -> Option<Location> {
-        let len = self.locations.len();
-        let address_as_usize = address.to_usize();
+        // and the one right after
+        if let Some(after_size) = self.get_retained_size(location + size) {
+            self.take_retained(location + size, after_size);
+            size += after_size;
+        }
 
-        if address_as_usize < len - 1 {
-            let state = self.locations[address_as_usize];
+        self.keep_retained(location, size);
+    }
 
-            match state {
-                State::Occupied(value) => Some(value),
-                State::Retained(_) => None
-            }
-        }else {
-            None
+    pub fn get_location(&self, address: Address) -> Option<Location> {
+        match self.estates.get(address.to_usize()) {
+            Some(Some((location, _))) => Some(*location),
+            _ => None
         }
     }
 
     pub fn get_size(&self, address: Address) -> Option<Size> {
-        let len = self.locations.len();
-        let address_as_usize = address.to_usize();
-
-        if address_as_usize < len - 1 {
-            let state = self.locations[address_as_usize];
-            let next_state = &self.locations[address_as_usize + 1];
-
-            match state {
-                State::Occupied(value) => {
-                    match next_state {
-                        State::Occupied(next_value) | State::Retained(next_value) => {
-                            Some(next_value - value)
-                        }
-                    }
-                },
-                State::Retained(_) => {
-                    None
-                }
-            }
-        }else {
-            None
-        }    
+        match self.estates.get(address.to_usize()) {
+            Some(Some((_, size))) => Some(*size),
+            _ => None
+        }
     }
 
+    // Estates found on a scan are registered one after another from 0
     pub fn register(&mut self, location: Location, size: Size) -> Address {
-        let len = self.locations.len();
-        let last_state = self.locations[len - 1];
-
-        match last_state {
-            State::Occupied(last) | State::Retained(last) => {
-                if location == last {
-                    self.locations[len - 1] = State::Occupied(last);
-                    self.locations.push(State::Retained(location + size));
-                    Address::from_usize(len - 1)
-                }else {
-                    panic!("Location should match the last estate location and size")
-                }        
-            }
+        if location == self.end {
+            self.end += size;
+            self.occupy(location, size)
+        }else {
+            panic!("Location should match the last estate location and size")
         }
     }
 }
@@ -344,6 +373,221 @@ mod tests {
         }
     }
 
+    mod trim {
+        use super::*;
+
+        #[test]
+        fn tail() {
+            let mut farming: Farming<u32> = Farming::new();
+            let first = farming.allot(2);
+            let second = farming.allot(3);
+            let third = farming.allot(4);
+
+            // a hole between occupied estates stays
+            farming.retain(second);
+
+            assert_eq!(farming.trim(), 9);
+
+            farming.retain(third);
+
+            assert_eq!(farming.get_retained_total(), 7);
+            assert_eq!(farming.get_last(), Some(first));
+            assert_eq!(farming.trim(), 2);
+            assert_eq!(farming.get_last(), Some(first));
+            assert_eq!(farming.get_retained_total(), 0);
+
+            // retained ones trimmed are not handed out again
+            assert_eq!(farming.allot_retained_below(3, 10), None);
+
+            let fourth = farming.allot(4);
+
+            assert_eq!(farming.get_location(fourth), Some(2));
+
+            farming.retain(first);
+            farming.retain(fourth);
+
+            assert_eq!(farming.trim(), 0);
+            assert_eq!(farming.get_last(), None);
+        }
+
+        #[test]
+        fn retained_below() {
+            let mut farming: Farming<u32> = Farming::new();
+            let first = farming.allot(2);
+
+            farming.allot(1);
+
+            let second = farming.allot(2);
+
+            farming.allot(1);
+            farming.retain(first);
+            farming.retain(second);
+
+            // second lies at 3, so only first is below it
+            let below = farming.allot_retained_below(2, 3).unwrap();
+
+            assert_eq!(farming.get_location(below), Some(0));
+            assert_eq!(farming.allot_retained_below(2, 3), None);
+
+            let next = farming.allot_retained_below(2, 6).unwrap();
+
+            assert_eq!(farming.get_location(next), Some(3));
+        }
+    }
+
+    mod fit {
+        use super::*;
+
+        // Occupied and retained estates cover the land in use without
+        // overlapping, and no two retained estates are neighbours.
+        fn check(farming: &Farming<u32>) {
+            let mut estates: Vec<(Location, Size, bool)> = farming.retained.iter().map(|(location, size)| (*location, *size, true)).collect();
+
+            for (location, address) in &farming.occupied {
+                estates.push((*location, farming.get_size(*address).unwrap(), false));
+            }
+
+            estates.sort();
+
+            let mut end = 0;
+            let mut was_retained = false;
+
+            for (location, size, is_retained) in estates {
+                assert_eq!(location, end);
+                assert!(!(was_retained && is_retained));
+
+                end = location + size;
+                was_retained = is_retained;
+            }
+
+            assert_eq!(end, farming.get_end());
+            assert_eq!(farming.fits.len(), farming.retained.len());
+        }
+
+        // Share of the land in use which is retained
+        fn waste(farming: &Farming<u32>) -> f64 {
+            let retained: usize = farming.retained.values().sum();
+
+            retained as f64 / farming.get_end().max(1) as f64
+        }
+
+        #[test]
+        fn split() {
+            let mut farming: Farming<u32> = Farming::new();
+            let large = farming.allot(10);
+
+            farming.allot(1);
+            farming.retain(large);
+
+            let small = farming.allot(3);
+            let rest = farming.allot(7);
+
+            assert_eq!(farming.get_location(small), Some(0));
+            assert_eq!(farming.get_location(rest), Some(3));
+            assert_eq!(farming.get_end(), 11);
+
+            // smallest retained estate which fits is taken
+            let eight = farming.allot(8);
+
+            farming.allot(1);
+
+            let six = farming.allot(6);
+
+            farming.allot(1);
+            farming.retain(eight);
+            farming.retain(six);
+
+            let best = farming.allot(5);
+
+            assert_eq!(farming.get_location(best), Some(20));
+            assert_eq!(farming.get_retained_size(25), Some(1));
+            check(&farming);
+        }
+
+        #[test]
+        fn merge() {
+            let mut farming: Farming<u32> = Farming::new();
+            let addresses: Vec<u32> = (0..3).map(|_| farming.allot(4)).collect();
+
+            farming.allot(1);
+            farming.retain(addresses[0]);
+            farming.retain(addresses[2]);
+            farming.retain(addresses[1]);
+
+            assert_eq!(farming.get_retained_size(0), Some(12));
+
+            let whole = farming.allot(12);
+
+            assert_eq!(farming.get_location(whole), Some(0));
+            assert_eq!(farming.get_end(), 13);
+            check(&farming);
+        }
+
+        #[test]
+        fn register_scan() {
+            // a scan registers every estate, then retains removed ones
+            let mut farming: Farming<u32> = Farming::new();
+
+            for (location, size, is_removed) in [(0, 3, true), (3, 2, true), (5, 4, false), (9, 6, true)] {
+                let address = farming.register(location, size);
+
+                if is_removed {
+                    farming.retain(address);
+                }
+            }
+
+            assert_eq!(farming.get_retained_size(0), Some(5));
+            assert_eq!(farming.get_retained_size(9), Some(6));
+            assert_eq!(farming.trim(), 9);
+            check(&farming);
+        }
+
+        #[test]
+        fn fragmentation() {
+            let mut rng = rand::thread_rng();
+            let mut farming: Farming<u32> = Farming::new();
+            let mut live: Vec<u32> = Vec::new();
+            let mut live_size = 0;
+            let mut peak_size = 0;
+
+            for round in 0..20_000 {
+                // grows for a while, then churns around the same size
+                if live.is_empty() || (round < 5_000 || rng.gen_bool(0.5)) && live.len() < 2_000 {
+                    let size = rng.gen_range(1..=50);
+
+                    live.push(farming.allot(size));
+                    live_size += size;
+                }else {
+                    let address = live.swap_remove(rng.gen_range(0..live.len()));
+
+                    live_size -= farming.get_size(address).unwrap();
+                    farming.retain(address);
+                }
+
+                peak_size = peak_size.max(live_size);
+
+                if round % 1_000 == 0 {
+                    check(&farming);
+                }
+            }
+
+            check(&farming);
+
+            // land never runs far past the most ever in use,
+            // and little of it is left in retained pieces
+            assert!(farming.get_end() < peak_size * 5 / 4, "end {} peak {}", farming.get_end(), peak_size);
+            assert!(waste(&farming) < 0.3, "waste {}", waste(&farming));
+
+            for address in live {
+                farming.retain(address);
+            }
+
+            // all of it merges back into one and goes with the trim
+            assert_eq!(farming.retained.len(), 1);
+            assert_eq!(farming.trim(), 0);
+        }
+    }
+
     mod register {
         use super::*;
 
@@ -3,7 +3,8 @@
     - Imagine a big Land broken into smaller blocks
     - A seqential number of x blocks is called a Estate.
     - A estate can be alloted or retained.
-    - Two adjacent retained estate cannot be merged.
+    - Two adjacent retained farming estates are merged, and a retained
+      farming estate larger than asked for is split.
     - A Address will be given when a estate has been alloted.
     - And a Address is needed for a estate to be retained in future.
     - It may happen that a new market needs to be created by old data. 
@@ -1,17 +1,26 @@
 use warehouse::Merchandise;
+use decimal::Spec;
+use fumble::Fumble;
+use crate::{ Numeral, ReadNumeral, push_numeral, read_numeral, read_legacy_numeral };
 type Good = Vec<u8>;
 
+// Literal can never hold a null byte. So a declared
+// spec is put after it, following a null byte.
+const SPEC_SEPARATOR: u8 = 0;
+
 #[derive(Clone)]
 pub struct Finfo {
-    numeral: u8,
-    literal: String
+    numeral: Numeral,
+    literal: String,
+    o_spec: Option<Spec>
 }
 
 impl Finfo {
-    pub fn new<S: AsRef<str>>(numeral: u8, literal: S) -> Self {
+    pub fn new<S: AsRef<str>>(numeral: Numeral, literal: S) -> Self {
         Self {
             numeral,
-            literal: literal.as_ref().to_owned()
+            literal: literal.as_ref().to_owned(),
+            o_spec: None
         }
     }
 
@@ -19,31 +28,130 @@ impl Finfo {
         self.literal.clone()
     }
 
-    pub fn get_numeral(&self) -> u8 {
+    pub fn set_literal<S: AsRef<str>>(&mut self, literal: S) {
+        self.literal = literal.as_ref().to_owned();
+    }
+
+    pub fn get_numeral(&self) -> Numeral {
         self.numeral
     }
+
+    pub fn get_spec(&self) -> Option<Spec> {
+        self.o_spec
+    }
+
+    pub fn set_spec(&mut self, spec: Spec) {
+        self.o_spec = Some(spec);
+    }
 }
 
 impl Merchandise for Finfo {
     fn to_good(&self) -> Vec<u8> {
         let mut good: Good = Good::new();
         let literal_as_bytes = self.literal.as_bytes();
-        let numeral = self.numeral;
-        
-        good.push(numeral);
+
+        push_numeral(&mut good, self.numeral);
         good.extend(literal_as_bytes);
 
+        if let Some(spec) = self.o_spec {
+            good.push(SPEC_SEPARATOR);
+            good.extend(spec.as_bytes());
+        }
+
         good
     }
 
-    fn from_good(good: Vec<u8>) -> Self {
+    fn from_good(good: Vec<u8>) -> Result<Self, Fumble> {
+        Self::decode(&good, read_numeral)
+    }
+}
+
+impl Finfo {
+    pub fn from_legacy_good(good: &[u8]) -> Result<Self, Fumble> {
+        Self::decode(good, read_legacy_numeral)
+    }
+
+    fn decode(good: &[u8], read: ReadNumeral) -> Result<Self, Fumble> {
         let len = good.len();
-        let numeral: u8 = good[0];
-        let literal: String = String::from_utf8(good[1..len].to_vec()).unwrap();
+        let mut literal_start = 0;
+        let numeral = match read(good, &mut literal_start) {
+            Some(numeral) => numeral,
+            None => return Err(Fumble::Corrupted)
+        };
+        let o_separator = good.iter().skip(literal_start).position(|byte| *byte == SPEC_SEPARATOR);
+        let mut literal_end = len;
+        let mut o_spec: Option<Spec> = None;
 
-        Self {
-            numeral,
-            literal
+        if let Some(position) = o_separator {
+            literal_end = literal_start + position;
+
+            match Spec::from_bytes(&good[(literal_end + 1)..len]) {
+                Ok(spec) => o_spec = Some(spec),
+                Err(_) => return Err(Fumble::Corrupted)
+            }
+        }
+
+        match String::from_utf8(good[literal_start..literal_end].to_vec()) {
+            Ok(literal) => Ok(Self {
+                numeral,
+                literal,
+                o_spec
+            }),
+            Err(_) => Err(Fumble::Corrupted)
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use decimal::Rounding;
+
+    #[test]
+    fn codec() {
+        let finfo = Finfo::new(3, "price");
+        let decoded = Finfo::from_good(finfo.to_good()).unwrap();
+
+        assert_eq!(decoded.get_numeral(), 3);
+        assert_eq!(decoded.get_literal(), "price");
+        assert_eq!(decoded.get_spec(), None);
+
+        let mut finfo = Finfo::new(3, "price");
+        let spec = Spec::new(10, 2, Rounding::HalfUp).unwrap();
+        finfo.set_spec(spec);
+        let decoded = Finfo::from_good(finfo.to_good()).unwrap();
+
+        assert_eq!(decoded.get_literal(), "price");
+        assert_eq!(decoded.get_spec(), Some(spec));
+        assert!(Finfo::from_good(Vec::new()).is_err());
+        assert!(Finfo::from_good(vec![3, 0xFF, 0xFE]).is_err());
+        assert!(Finfo::from_good(vec![3, b'a', SPEC_SEPARATOR, 2]).is_err());
+
+        // numerals past a byte
+        let mut finfo = Finfo::new(300, "price");
+        finfo.set_spec(spec);
+        let decoded = Finfo::from_good(finfo.to_good()).unwrap();
+
+        assert_eq!(decoded.get_numeral(), 300);
+        assert_eq!(decoded.get_literal(), "price");
+        assert_eq!(decoded.get_spec(), Some(spec));
+        assert!(Finfo::from_good(vec![0x80, 0x80, 0x04, b'a']).is_err());
+    }
+
+    #[test]
+    fn legacy() {
+        let spec = Spec::new(10, 2, Rounding::HalfUp).unwrap();
+        let mut good = vec![200];
+        good.extend(b"price");
+        good.push(SPEC_SEPARATOR);
+        good.extend(spec.as_bytes());
+
+        let decoded = Finfo::from_legacy_good(&good).unwrap();
+
+        assert_eq!(decoded.get_numeral(), 200);
+        assert_eq!(decoded.get_literal(), "price");
+        assert_eq!(decoded.get_spec(), Some(spec));
+        assert_eq!(decoded.to_good()[..2], [0xC8, 0x01]);
+        assert!(Finfo::from_legacy_good(&[]).is_err());
+    }
+}
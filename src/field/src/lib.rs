@@ -1,7 +1,17 @@
 mod finfo;
 use finfo::Finfo;
-use warehouse::{Warehouse, Token, Inventory, Mode as WarehouseMode, SessionMode, SessionItem};
+use warehouse::{Warehouse, Token, Inventory, Merchandise, Mode as WarehouseMode, SessionMode, SessionItem};
 use std::collections::HashMap;
+use std::time::Duration;
+use decimal::Spec;
+use fumble::Fumble;
+use ds::varint;
+
+// Numeral stands in for a field literal wherever
+// it is stored, written as a varint on disk.
+pub type Numeral = u16;
+// Reads a numeral from pos and moves pos past it
+pub type ReadNumeral = fn(&[u8], &mut usize) -> Option<Numeral>;
 
 const ID_LITERAL: &str = "_id";
 const FIELD_DATA_FILE_NAME: &str = "field";
@@ -12,10 +22,28 @@ pub enum Mode {
     WithInventory
 }
 
+pub fn push_numeral(bytes: &mut Vec<u8>, numeral: Numeral) {
+    varint::push(bytes, numeral as u32)
+}
+
+pub fn read_numeral(bytes: &[u8], pos: &mut usize) -> Option<Numeral> {
+    varint::read(bytes, pos).and_then(|value| Numeral::try_from(value).ok())
+}
+
+// Numeral was a single byte before it was widened
+pub fn read_legacy_numeral(bytes: &[u8], pos: &mut usize) -> Option<Numeral> {
+    let numeral = *bytes.get(*pos)? as Numeral;
+
+    *pos += 1;
+
+    Some(numeral)
+}
+
 pub struct Field {
-    warehouse: Warehouse<u8, Finfo>,
-    token_book: Inventory<u8, Token<u8>>,
-    literal_book: HashMap<String, u8>
+    warehouse: Warehouse<Numeral, Finfo>,
+    token_book: Inventory<Numeral, Token<Numeral>>,
+    literal_book: HashMap<String, Numeral>,
+    spec_book: HashMap<Numeral, Spec>
 }
 
 impl Field {
@@ -50,10 +78,19 @@ impl Field {
                 match session_item {
                     SessionItem::WithToken(token, finfo) => {
                         let numeral = finfo.get_numeral();
+
+                        // Stored once, the same as records
+                        if self.token_book.get(numeral).is_some() {
+                            panic!("Field {} is stored twice, the field file needs a look", numeral);
+                        }
                         
                         self.token_book.reserve(Some(numeral));
                         self.token_book.replace(numeral, token);
                         self.literal_book.insert(finfo.get_literal(), numeral);
+
+                        if let Some(spec) = finfo.get_spec() {
+                            self.spec_book.insert(numeral, spec);
+                        }
                     },
                     _ => panic!()
                 }
@@ -61,15 +98,30 @@ impl Field {
         }
         
         self.warehouse.stop_session();
+
+        // Numerals of removed fields are left as holes,
+        // which are handed out again like any freed one.
+        let top = self.literal_book.values().max().copied().unwrap_or(0);
+
+        for numeral in 0..top {
+            if self.token_book.get(numeral).is_none() {
+                self.token_book.remove(numeral);
+            }
+        }
     }
 
     pub fn new(mode: Mode) -> Self{
-        let mut warehouse: Warehouse<u8, Finfo> = Warehouse::new(WarehouseMode::Inventory);
-        let token_book: Inventory<u8, Token<u8>> = Inventory::new();
+        Self::with_file_name(mode, FIELD_DATA_FILE_NAME)
+    }
+
+    // Each collection keeps its fields in a file of its own
+    pub fn with_file_name(mode: Mode, file_name: &str) -> Self{
+        let mut warehouse: Warehouse<Numeral, Finfo> = Warehouse::new(WarehouseMode::Inventory);
+        let token_book: Inventory<Numeral, Token<Numeral>> = Inventory::new();
 
         match mode {
             Mode::WithInventory => {
-                warehouse = Warehouse::new(WarehouseMode::Both(FIELD_DATA_FILE_NAME.to_string()));
+                warehouse = Warehouse::new(WarehouseMode::Both(file_name.to_string()));
             },
             _ => {}
         }
@@ -77,7 +129,8 @@ impl Field {
         let mut s = Self {
             warehouse,
             token_book,
-            literal_book: HashMap::new()
+            literal_book: HashMap::new(),
+            spec_book: HashMap::new()
         };   
 
         if mode == Mode::WithInventory {
@@ -89,18 +142,18 @@ impl Field {
         s    
     }
 
-    pub fn get_literal(&mut self, numeral: u8) -> Option<String> {
+    pub fn get_literal(&mut self, numeral: Numeral) -> Option<String> {
         let token = self.token_book.get(numeral).unwrap();
         let finfo = self.warehouse.get(token).unwrap();
             
         Some(finfo.get_literal())
     }
 
-    pub fn get_numeral(&self, literal: &str) -> Option<&u8> {
+    pub fn get_numeral(&self, literal: &str) -> Option<&Numeral> {
         self.literal_book.get(literal)
     }
 
-    pub fn add(&mut self, literal: &str) -> u8{
+    pub fn add(&mut self, literal: &str) -> Numeral{
         // check if literal already exists
         if let Some(numeral) = self.literal_book.get(literal) {
             *numeral  
@@ -116,35 +169,179 @@ impl Field {
         }
     }
 
-    pub fn update(&mut self, old_literal: &str, new_literal: &str){
+    // Books are changed only once the file is,
+    // so a failed update leaves the field as it was.
+    pub fn update(&mut self, old_literal: &str, new_literal: &str) -> Result<(), Fumble> {
         if old_literal == new_literal {
             panic!("Liteals are same")
         }
 
-        let o_numeral = self.literal_book.remove(&old_literal.to_string());
+        let o_numeral = self.literal_book.get(old_literal).copied();
         
         if let Some(numeral) = o_numeral {
-            let updated_finfo: Finfo = Finfo::new(numeral, new_literal);
             let token = self.token_book.get(numeral).unwrap();
+            let mut updated_finfo: Finfo = self.warehouse.get(token)?;
+            updated_finfo.set_literal(new_literal);
             
-            let updated_token = self.warehouse.update(token, updated_finfo).ok().unwrap();
+            let updated_token = self.warehouse.update(token, updated_finfo)?;
+            self.literal_book.remove(old_literal);
             self.literal_book.insert(new_literal.to_owned(), numeral);
             self.token_book.replace(numeral, updated_token);
+
+            Ok(())
         }else {
             panic!("Literal not found")
         }
     }
 
-    // fn remove(&mut self, numeral: u8){
-    //     let literal = self.get_literal(numeral).unwrap();
-    //     let token = self.token_book.take(numeral).unwrap();
-        
-    //     self.literal_book.remove(&literal);
-    //     self.warehouse.remove(token);
+    pub fn get_spec(&self, numeral: Numeral) -> Option<&Spec> {
+        self.spec_book.get(&numeral)
+    }
+
+    // Declares decimal spec for the literal. A new field
+    // is added if the literal doesn't exist yet.
+    pub fn set_spec(&mut self, literal: &str, spec: Spec) -> Numeral {
+        let numeral = self.add(literal);
+        let token = self.token_book.get(numeral).unwrap();
+        let mut finfo: Finfo = self.warehouse.get(token).unwrap();
         
-    // }
+        finfo.set_spec(spec);
+
+        let updated_token = self.warehouse.update(token, finfo).ok().unwrap();
+        self.token_book.replace(numeral, updated_token);
+        self.spec_book.insert(numeral, spec);
 
-    pub fn get_total(&self) -> u8 {
-        self.literal_book.len() as u8
+        numeral
     }
-}
\ No newline at end of file
+
+    pub fn get_numerals(&self) -> Vec<Numeral> {
+        self.literal_book.values().copied().collect()
+    }
+
+    // Takes the field out of the file and frees its
+    // numeral, to be handed to the next field added.
+    pub fn remove(&mut self, numeral: Numeral) -> Result<(), Fumble> {
+        let literal = self.get_literal(numeral).unwrap();
+
+        self.warehouse.remove(self.token_book.get(numeral).unwrap())?;
+        self.token_book.take(numeral);
+        self.token_book.remove(numeral);
+        self.literal_book.remove(&literal);
+        self.spec_book.remove(&numeral);
+
+        Ok(())
+    }
+
+    // Relocates the token of every field compaction moved
+    pub fn compact(&mut self, steps: usize) -> Result<(), Fumble> {
+        for (source, target, finfo) in self.warehouse.compact(steps, |_| true)? {
+            let numeral = finfo.get_numeral();
+
+            if let Some(token) = self.token_book.get(numeral) {
+                if token.get_godown_address() == Some(source) {
+                    let relocated = token.relocate(target);
+
+                    self.token_book.replace(numeral, relocated);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn sync_due(&mut self) -> Result<(), Fumble> {
+        self.warehouse.sync_due()
+    }
+
+    pub fn is_durable(&self) -> bool {
+        self.warehouse.is_durable()
+    }
+
+    pub fn get_sync_wait(&self) -> Option<Duration> {
+        self.warehouse.get_sync_wait()
+    }
+
+    pub fn is_hollow(&self) -> bool {
+        self.warehouse.is_hollow()
+    }
+
+    pub fn get_total(&self) -> Numeral {
+        self.literal_book.len() as Numeral
+    }
+
+    // Rewrites a field file from before numerals were widened,
+    // aside for warehouse::swap to put in its place. Gives how
+    // many fields could not be read and were left behind.
+    pub fn migrate(file_name: &str) -> Result<usize, Fumble> {
+        warehouse::rewrite(file_name, |good| {
+            Finfo::from_legacy_good(good).map(|finfo| finfo.to_good())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::time::{ SystemTime, UNIX_EPOCH };
+
+    fn create_file_name() -> String {
+        setup::env::set_folder_path(env::temp_dir().to_str());
+
+        format!("field{}", SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos())
+    }
+
+    fn remove_files(file_name: &str) {
+        setup::remove_file(file_name);
+        setup::remove_file(&warehouse::ledger_file_name(file_name));
+    }
+
+    #[test]
+    fn remove() {
+        let file_name = create_file_name();
+        let mut field = Field::with_file_name(Mode::WithInventory, &file_name);
+        let first = field.add("first");
+        let second = field.add("second");
+
+        field.set_spec("second", Spec::new(10, 2, decimal::Rounding::HalfEven).unwrap());
+        field.remove(second).unwrap();
+
+        let is_gone = field.get_numeral("second").is_none() && field.get_spec(second).is_none();
+        // freed numeral goes to the next field
+        let third = field.add("third");
+        let literal = field.get_literal(third);
+
+        drop(field);
+        remove_files(&file_name);
+
+        assert!(is_gone);
+        assert_eq!(third, second);
+        assert_eq!(literal, Some(String::from("third")));
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn restart() {
+        let file_name = create_file_name();
+        let mut field = Field::with_file_name(Mode::WithInventory, &file_name);
+        let first = field.add("first");
+        let second = field.add("second");
+        let last = field.add("last");
+
+        field.remove(second).unwrap();
+        drop(field);
+
+        // hole left by the removed field is restored as free
+        let mut field = Field::with_file_name(Mode::WithInventory, &file_name);
+        let numerals = (field.get_numeral("first").copied(), field.get_numeral("second").copied(), field.get_numeral("last").copied());
+        let third = field.add("third");
+        let fourth = field.add("fourth");
+
+        drop(field);
+        remove_files(&file_name);
+
+        assert_eq!(numerals, (Some(first), None, Some(last)));
+        assert_eq!(third, second);
+        assert_eq!(fourth, last + 1);
+    }
+}
@@ -7,11 +7,24 @@ pub enum Fumble {
     ScalarBoolean,
     ScalarDecimal,
     ScalarInteger,
+    ScalarPrecision,
     ScalarsCombinedSize,
     Operator,
     Start,
     Action,
-    Identifier
+    Identifier,
+    Declaration,
+    Corrupted,
+    Index,
+    Unique,
+    Duplicates(String),
+    Collection,
+    Rename,
+    Disk,
+    // Nothing kept where a token points
+    Missing,
+    // What the schema turned down
+    Schema(String)
 }
 
 impl Fumble {
@@ -47,6 +60,10 @@ impl Fumble {
                 result = "Cannot parse integer value"
             },
 
+            Fumble::ScalarPrecision => {
+                result = "Decimal value exceeds the declared precision"
+            },
+
             Fumble::ScalarsCombinedSize => {
                 result = "Data size limit exceeds"
             },
@@ -56,7 +73,7 @@ impl Fumble {
             },
 
             Fumble::Start => {
-                result = "Query should either start with 'create' or 'match'"
+                result = "Query should either start with 'create', 'match', 'declare', 'drop', 'list', 'verify', 'use', 'schema' or 'rename'"
             },
 
             Fumble::Action => {
@@ -65,6 +82,46 @@ impl Fumble {
 
             Fumble::Identifier => {
                 result = "Cannot set _id"
+            },
+
+            Fumble::Declaration => {
+                result = "'declare' should be followed by field, 'decimal', precision, scale and optionally 'half_even', 'half_up' or 'truncate'"
+            },
+
+            Fumble::Corrupted => {
+                result = "Stored data is corrupted"
+            },
+
+            Fumble::Index => {
+                result = "'create index' and 'drop index' should be followed by distinct fields other than _id, a unique or text index takes only one, and fields indexed one way take no other index"
+            },
+
+            Fumble::Unique => {
+                result = "Value already exists for a unique field"
+            },
+
+            Fumble::Duplicates(duplicates) => {
+                result = duplicates.as_str()
+            },
+
+            Fumble::Collection => {
+                result = "Collection should be created before use, named like a field, and a query should be on one collection only"
+            },
+
+            Fumble::Rename => {
+                result = "'rename field' should be followed by a field other than _id and a new name no other field has"
+            },
+
+            Fumble::Disk => {
+                result = "Could not write to disk"
+            },
+
+            Fumble::Missing => {
+                result = "Stored data could not be found"
+            },
+
+            Fumble::Schema(message) => {
+                result = message.as_str()
             }
         }
 
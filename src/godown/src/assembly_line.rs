@@ -2,6 +2,8 @@ use crate::def::{ Good, Package };
 use crate::block::Block;
 use crate::flag::FlagType;
 use crate::labeller::{ LabelInfo, Labeller };
+use crate::compressor::Compressor;
+use fumble::Fumble;
 
 #[derive(Debug, PartialEq)]
 pub enum Integrity {
@@ -10,23 +12,19 @@ pub enum Integrity {
 }
 
 impl Integrity {
-    pub fn unwrap(self) -> Good {
+    pub fn into_good(self) -> Result<Good, Fumble> {
         match self {
-            Self::Consistent(good) => good,
-            Self::Inconsistent => panic!("Good is inconsistent")
+            Self::Consistent(good) => Ok(good),
+            Self::Inconsistent => Err(Fumble::Corrupted)
         }
     }
 }
 
+// Packages from before checksums end their good with this byte
 struct Consistency;
 impl Consistency {
-    fn add_consistent_byte(good: &mut Good){
-        good.push(u8::MAX);
-    }
-
     fn check(good: &Good) -> bool {
-        let len = good.len();
-        let o_last = good.get(len - 1);
+        let o_last = good.last();
 
         if let Some(last) = o_last {
             if last == &u8::MAX {
@@ -43,16 +41,26 @@ impl Consistency {
 pub struct AssemblyLine;
 
 impl AssemblyLine {
-    pub fn assemble(mut good: Good) -> Package {
+    // Good is kept compressed only if that takes fewer blocks,
+    // so a good fitting in one block is never compressed.
+    pub fn assemble(mut good: Good, compression: bool) -> Package {
         if good.len() == 0 {
             panic!("Good cannot be empty")
         }
 
-        // add consistency byte
-        Consistency::add_consistent_byte(&mut good);
+        let header_size = Labeller::get_header_size();
+        let mut is_compressed = false;
+
+        if compression && Block::get_count(header_size + good.len()) > 1 {
+            let compressed = Compressor::compress(&good);
+
+            if Block::get_count(header_size + compressed.len()) < Block::get_count(header_size + good.len()) {
+                good = compressed;
+                is_compressed = true;
+            }
+        }
 
         let good_len = good.len();
-        let header_size = Labeller::get_header_size();
 
         // extend package to size of header + good
         let mut package: Package = Package::new();
@@ -69,28 +77,46 @@ impl AssemblyLine {
         let label_info = LabelInfo {
             block_count: block_count as u16,
             flag_type: FlagType::Insert,
-            good_len: good_len as u32
+            good_len: good_len as u32,
+            is_compressed,
+            is_checksummed: true
         };
 
         Labeller::label(&mut package, label_info);
+        Labeller::seal(&mut package);
 
         package
     }
 
     pub fn dismantle(package: &[u8]) -> Integrity {
         let label_info = Labeller::read(package);
-        let header_size = Labeller::get_header_size();
+        let good_start = Labeller::get_good_start(&label_info);
+        let good_end = good_start + (label_info.good_len as usize);
 
-        let mut good = package[header_size..(header_size + (label_info.good_len as usize))].to_vec();
+        // A torn write may leave a length running past the package
+        if good_end > package.len() {
+            return Integrity::Inconsistent;
+        }
 
-        let is_consistent = Consistency::check(&good);
+        let mut good = package[good_start..good_end].to_vec();
 
-        if is_consistent == true {
+        if label_info.is_checksummed {
+            if !Labeller::verify(package) {
+                return Integrity::Inconsistent;
+            }
+        }else if Consistency::check(&good) {
             good.pop();
-
-            Integrity::Consistent(good)
         }else {
-            Integrity::Inconsistent
+            return Integrity::Inconsistent;
+        }
+
+        if !label_info.is_compressed {
+            return Integrity::Consistent(good);
+        }
+
+        match Compressor::decompress(&good) {
+            Some(decompressed) => Integrity::Consistent(decompressed),
+            None => Integrity::Inconsistent
         }
     }
 }
@@ -105,7 +131,7 @@ mod tests {
         #[test]
         #[should_panic]
         fn empty_good(){
-            AssemblyLine::assemble(Vec::<u8>::new());
+            AssemblyLine::assemble(Vec::<u8>::new(), false);
         }
     }
 
@@ -124,11 +150,11 @@ mod tests {
             let random_len = rand::thread_rng().gen_range(1..1000) as usize;
             let mut good: Vec<u8> = Vec::new();
 
-            good.resize(random_len, 0);
+            good.resize(random_len, 1);
 
-            let mut package = AssemblyLine::assemble(good);
+            let mut package = AssemblyLine::assemble(good, false);
 
-            // remove the consistent byte
+            // wipe the good under the checksum
             for index in Labeller::get_header_size()..package.len() {
                 package[index] = 0;
             }
@@ -145,7 +171,7 @@ mod tests {
 
             good.resize(random_len, 0);
 
-            let package = AssemblyLine::assemble(good.clone());
+            let package = AssemblyLine::assemble(good.clone(), false);
 
             let integrity = AssemblyLine::dismantle(&package);
             
@@ -155,5 +181,51 @@ mod tests {
                 assert!(false)
             }
         }
+
+        #[test]
+        fn torn() {
+            let good: Good = (0..1000).map(|index| index as u8).collect();
+            let package = AssemblyLine::assemble(good, false);
+
+            // any byte of header or good going wrong is caught
+            for index in 0..(Labeller::get_header_size() + 1000) {
+                let mut torn = package.clone();
+                torn[index] ^= 0x01;
+
+                assert_eq!(AssemblyLine::dismantle(&torn), Integrity::Inconsistent);
+            }
+
+            assert!(AssemblyLine::dismantle(&package).into_good().is_ok());
+        }
+
+        #[test]
+        fn legacy() {
+            // [block count][flag][good len] then good and consistency byte
+            let mut package: Package = vec![1, 0, 1, 4, 0, 0, 0, 7, 8, 9, u8::MAX];
+            package.resize(256, 0);
+
+            assert_eq!(AssemblyLine::dismantle(&package), Integrity::Consistent(vec![7, 8, 9]));
+
+            package[10] = 0;
+
+            assert!(matches!(AssemblyLine::dismantle(&package).into_good(), Err(Fumble::Corrupted)));
+        }
+
+        #[test]
+        fn compressed() {
+            let good: Good = b"name\0#hello world\0".iter().cycle().take(5000).copied().collect();
+            let package = AssemblyLine::assemble(good.clone(), true);
+
+            assert!(Labeller::read(&package).is_compressed);
+            assert!(package.len() < AssemblyLine::assemble(good.clone(), false).len());
+            assert_eq!(AssemblyLine::dismantle(&package), Integrity::Consistent(good));
+
+            // nothing to save in a single block
+            let small: Good = vec![0; 100];
+            let package = AssemblyLine::assemble(small.clone(), true);
+
+            assert!(!Labeller::read(&package).is_compressed);
+            assert_eq!(AssemblyLine::dismantle(&package), Integrity::Consistent(small));
+        }
     }
-}
\ No newline at end of file
+}
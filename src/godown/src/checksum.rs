@@ -0,0 +1,55 @@
+//! CRC32C (Castagnoli), bit reflected, one table lookup per byte.
+//! Parts are checksummed as if they were one run of bytes.
+
+const POLYNOMIAL: u32 = 0x82F6_3B78;
+const TABLE: [u32; 256] = make_table();
+
+const fn make_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut index = 0;
+
+    while index < 256 {
+        let mut crc = index as u32;
+        let mut bit = 0;
+
+        while bit < 8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ POLYNOMIAL } else { crc >> 1 };
+            bit += 1;
+        }
+
+        table[index] = crc;
+        index += 1;
+    }
+
+    table
+}
+
+pub struct Checksum;
+
+impl Checksum {
+    pub fn compute(parts: &[&[u8]]) -> u32 {
+        let mut crc = u32::MAX;
+
+        for part in parts {
+            for byte in part.iter() {
+                crc = TABLE[((crc ^ *byte as u32) & 0xFF) as usize] ^ (crc >> 8);
+            }
+        }
+
+        !crc
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compute() {
+        // check value of CRC32C
+        assert_eq!(Checksum::compute(&[b"123456789"]), 0xE306_9283);
+        assert_eq!(Checksum::compute(&[b"1234", b"", b"56789"]), 0xE306_9283);
+        assert_eq!(Checksum::compute(&[]), 0);
+        assert_ne!(Checksum::compute(&[b"123456788"]), 0xE306_9283);
+    }
+}
@@ -0,0 +1,178 @@
+//! Compressor is an LZSS codec for goods.
+//! Compressed bytes start with the length of the original as u32.
+//! Then each control byte tells for up to 8 items after it, lowest
+//! bit first, whether the item is a literal byte (0) or a match (1).
+//! A match is [offset u16][length - MIN_MATCH u8], copying that many
+//! bytes from offset bytes back in what has been written so far.
+
+const ORIGINAL_LEN_BYTE: usize = 4;
+const MIN_MATCH: usize = 4;
+const MAX_MATCH: usize = MIN_MATCH + u8::MAX as usize;
+const WINDOW: usize = u16::MAX as usize;
+const HASH_BITS: u32 = 14;
+const NO_POSITION: usize = usize::MAX;
+
+pub struct Compressor;
+
+impl Compressor {
+    // Spot of the 4 bytes at position in the table of last positions
+    fn hash(input: &[u8], position: usize) -> usize {
+        let value = u32::from_le_bytes([
+            input[position],
+            input[position + 1],
+            input[position + 2],
+            input[position + 3]
+        ]);
+
+        (value.wrapping_mul(2_654_435_761) >> (32 - HASH_BITS)) as usize
+    }
+
+    pub fn compress(input: &[u8]) -> Vec<u8> {
+        let len = input.len();
+        let mut output: Vec<u8> = Vec::with_capacity(len / 2);
+        let mut table: Vec<usize> = vec![NO_POSITION; 1 << HASH_BITS];
+        let mut position = 0;
+        // control byte is put in front of every 8 items
+        let mut control_at = 0;
+        let mut item = 8;
+
+        output.extend((len as u32).to_le_bytes());
+
+        while position < len {
+            if item == 8 {
+                control_at = output.len();
+                output.push(0);
+                item = 0;
+            }
+
+            let mut match_len = 0;
+            let mut offset = 0;
+
+            if position + MIN_MATCH <= len {
+                let hash = Self::hash(input, position);
+                let candidate = table[hash];
+
+                table[hash] = position;
+
+                if candidate != NO_POSITION && position - candidate <= WINDOW {
+                    let max = (len - position).min(MAX_MATCH);
+
+                    while match_len < max && input[candidate + match_len] == input[position + match_len] {
+                        match_len += 1;
+                    }
+
+                    offset = position - candidate;
+                }
+            }
+
+            if match_len >= MIN_MATCH {
+                output[control_at] |= 1 << item;
+                output.extend((offset as u16).to_le_bytes());
+                output.push((match_len - MIN_MATCH) as u8);
+
+                for skipped in (position + 1)..(position + match_len) {
+                    if skipped + MIN_MATCH <= len {
+                        table[Self::hash(input, skipped)] = skipped;
+                    }
+                }
+
+                position += match_len;
+            }else {
+                output.push(input[position]);
+                position += 1;
+            }
+
+            item += 1;
+        }
+
+        output
+    }
+
+    // None if bytes weren't written by compress
+    pub fn decompress(input: &[u8]) -> Option<Vec<u8>> {
+        let len = u32::from_le_bytes(input.get(0..ORIGINAL_LEN_BYTE)?.try_into().unwrap()) as usize;
+        // a match of 3 bytes is the most any bytes grow
+        let mut output: Vec<u8> = Vec::with_capacity(len.min(input.len() * MAX_MATCH / 3));
+        let mut position = ORIGINAL_LEN_BYTE;
+
+        while output.len() < len {
+            let control = *input.get(position)?;
+
+            position += 1;
+
+            for item in 0..8 {
+                if output.len() >= len {
+                    break;
+                }
+
+                if control & (1 << item) == 0 {
+                    output.push(*input.get(position)?);
+                    position += 1;
+                }else {
+                    let offset = u16::from_le_bytes([*input.get(position)?, *input.get(position + 1)?]) as usize;
+                    let match_len = *input.get(position + 2)? as usize + MIN_MATCH;
+
+                    if offset == 0 || offset > output.len() || output.len() + match_len > len {
+                        return None;
+                    }
+
+                    // Byte by byte, as a match may run into itself
+                    let start = output.len() - offset;
+
+                    for index in start..(start + match_len) {
+                        output.push(output[index]);
+                    }
+
+                    position += 3;
+                }
+            }
+        }
+
+        if position == input.len() {
+            Some(output)
+        }else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn codec() {
+        let mut rng = rand::thread_rng();
+        let repeated: Vec<u8> = b"name\0#hello world\0".iter().cycle().take(10_000).copied().collect();
+        let random: Vec<u8> = (0..10_000).map(|_| rng.gen()).collect();
+        let same: Vec<u8> = vec![7; 100_000];
+
+        for input in [Vec::new(), vec![1, 2, 3], repeated.clone(), random, same.clone()] {
+            assert_eq!(Compressor::decompress(&Compressor::compress(&input)), Some(input));
+        }
+
+        assert!(Compressor::compress(&repeated).len() < repeated.len() / 10);
+        assert!(Compressor::compress(&same).len() < same.len() / 50);
+    }
+
+    #[test]
+    fn corrupted() {
+        let mut rng = rand::thread_rng();
+        let input: Vec<u8> = b"price\0%12.50\0".iter().cycle().take(2_000).copied().collect();
+        let compressed = Compressor::compress(&input);
+
+        for len in 0..compressed.len() {
+            assert_eq!(Compressor::decompress(&compressed[..len]), None);
+        }
+
+        // Shouldn't panic, whatever the bytes are
+        for _ in 0..10_000 {
+            let mut corrupted = compressed.clone();
+            let index = rng.gen_range(0..corrupted.len());
+            corrupted[index] = rng.gen();
+
+            let _ = Compressor::decompress(&corrupted);
+        }
+    }
+}
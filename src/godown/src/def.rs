@@ -1,11 +1,24 @@
-pub const GOOD_MAX_SIZE: usize = 32 * 1024; // 32 kb
+// Block count is labelled as u16, so a package spans at most
+// 65535 blocks. A kb is kept aside for header and consistency byte.
+pub const GOOD_MAX_SIZE: usize = 16 * 1024 * 1024 - 1024; // just under 16 mb
 pub const BLOCK_SIZE: usize = 256; // 256 byte
-pub const CHUNK_SIZE: usize = GOOD_MAX_SIZE * 2; // 64 kb
+// A package larger than a chunk is read on its own
+pub const CHUNK_SIZE: usize = 64 * 1024; // 64 kb
+// Ledger is synced into the data file and emptied past this
+pub const LEDGER_MAX_SIZE: usize = 4 * 1024 * 1024; // 4 mb
+pub const LEDGER_EXTENSION: &str = "wal";
+// Compaction is worth it once holes take 1 in this many blocks
+pub const HOLLOW_SHARE: usize = 4;
 pub type Address = u64;
+// Old address, new address and the good of a package compaction moved
+pub type Move = (Address, Address, Good);
 pub type Package = Vec<u8>;
 pub type Good = Vec<u8>;
 
 pub enum Item {
     WithAddress(Address, Good),
-    WithoutAddress(Good)
+    WithoutAddress(Good),
+    // Package failing its checks, with its address when initializing.
+    // It is left where it is, and its estate stays occupied.
+    Corrupted(Option<Address>)
 }
\ No newline at end of file
@@ -6,14 +6,17 @@
 use std::fs::{File, OpenOptions};
 use std::io::{Read, Seek, SeekFrom};
 use std::os::unix::fs::FileExt;
+use fumble::Fumble;
 
 pub struct Disk(File);
 
 impl Disk {
     pub fn new(file_path: &str) -> Self {
+        // Not append, as on Linux positional writes to a file
+        // opened for append always land at the end.
         let file_result = OpenOptions::new()
         .read(true)
-        .append(true)
+        .write(true)
         .open(file_path);
 
         match file_result {
@@ -22,9 +25,32 @@ impl Disk {
         }
     }
 
-    pub fn put(&mut self, pos: usize, data: &Vec<u8>) {
-        let _ = self.0.seek(SeekFrom::Start(pos as u64));
-        let _ = self.0.write_at(data, pos as u64);
+    pub fn put(&mut self, pos: usize, data: &[u8]) -> Result<(), Fumble> {
+        match self.0.write_all_at(data, pos as u64) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(Fumble::Disk)
+        }
+    }
+
+    pub fn get_len(&self) -> usize {
+        match self.0.metadata() {
+            Ok(metadata) => metadata.len() as usize,
+            Err(_) => 0
+        }
+    }
+
+    pub fn truncate(&mut self, len: usize) -> Result<(), Fumble> {
+        match self.0.set_len(len as u64) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(Fumble::Disk)
+        }
+    }
+
+    pub fn sync(&mut self) -> Result<(), Fumble> {
+        match self.0.sync_data() {
+            Ok(_) => Ok(()),
+            Err(_) => Err(Fumble::Disk)
+        }
     }
 
     pub fn read(&mut self, pos: usize, len: usize) -> Vec<u8> {
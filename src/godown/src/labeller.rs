@@ -2,26 +2,71 @@
 // Assembly line will put 0 bytes value in the
 // given pointer position of given length
 use crate::flag::{ Flag, FlagType };
+use crate::checksum::Checksum;
 
 const TOTAL_BLOCK_BYTE: usize = 2;
 const TOTAL_FLAG_BYTE: usize = 1;
 const TOTAL_GOOD_LEN_BYTE: usize = 4;
+const TOTAL_CHECKSUM_BYTE: usize = 4;
 const BLOCK_BYTE_POINTER: usize = 0;
 const FLAG_BYTE_POINTER: usize = BLOCK_BYTE_POINTER + TOTAL_BLOCK_BYTE;
 const GOOD_LEN_BYTE_POINTER: usize = FLAG_BYTE_POINTER + TOTAL_FLAG_BYTE;
+const CHECKSUM_BYTE_POINTER: usize = GOOD_LEN_BYTE_POINTER + TOTAL_GOOD_LEN_BYTE;
+// Set in flag byte when good is kept compressed
+const COMPRESSED: u8 = 0x80;
+// Set in flag byte when header has a checksum. Packages from
+// before it end their good with a consistency byte instead.
+const CHECKSUMMED: u8 = 0x40;
 
 #[derive(Clone, Copy)]
 pub struct LabelInfo {
     pub block_count: u16,
     pub good_len: u32,
-    pub flag_type: FlagType
+    pub flag_type: FlagType,
+    pub is_compressed: bool,
+    pub is_checksummed: bool
 }
 
 pub struct Labeller;
 
 impl Labeller {
     pub fn get_header_size() -> usize {
-        TOTAL_BLOCK_BYTE + TOTAL_FLAG_BYTE + TOTAL_GOOD_LEN_BYTE
+        TOTAL_BLOCK_BYTE + TOTAL_FLAG_BYTE + TOTAL_GOOD_LEN_BYTE + TOTAL_CHECKSUM_BYTE
+    }
+
+    pub fn get_good_start(label_info: &LabelInfo) -> usize {
+        if label_info.is_checksummed {
+            Self::get_header_size()
+        }else {
+            CHECKSUM_BYTE_POINTER
+        }
+    }
+
+    // Covers the header before the checksum and the good
+    fn checksum(package: &[u8], good_len: usize) -> u32 {
+        let good_start = Self::get_header_size();
+
+        Checksum::compute(&[&package[..CHECKSUM_BYTE_POINTER], &package[good_start..(good_start + good_len)]])
+    }
+
+    // Puts the checksum in a package labelled as checksummed
+    pub fn seal(package: &mut [u8]) {
+        let good_len = Self::read(package).good_len as usize;
+        let checksum = Self::checksum(package, good_len);
+
+        package[CHECKSUM_BYTE_POINTER..(CHECKSUM_BYTE_POINTER + TOTAL_CHECKSUM_BYTE)].copy_from_slice(&checksum.to_le_bytes());
+    }
+
+    pub fn verify(package: &[u8]) -> bool {
+        let good_len = Self::read(package).good_len as usize;
+
+        if Self::get_header_size() + good_len > package.len() {
+            return false;
+        }
+
+        let checksum = u32::from_le_bytes(package[CHECKSUM_BYTE_POINTER..(CHECKSUM_BYTE_POINTER + TOTAL_CHECKSUM_BYTE)].try_into().unwrap());
+
+        Self::checksum(package, good_len) == checksum
     }
 
     pub fn label(package: &mut [u8], label_info: LabelInfo) {
@@ -35,7 +80,16 @@ impl Labeller {
         let good_len = label_info.good_len;
         let flag_type = label_info.flag_type;
         
-        let flag = Flag::get_value(flag_type);
+        let mut flag = Flag::get_value(flag_type);
+
+        if label_info.is_compressed {
+            flag |= COMPRESSED;
+        }
+
+        if label_info.is_checksummed {
+            flag |= CHECKSUMMED;
+        }
+
         let block_bytes = block_count.to_le_bytes();
         let good_len_bytes = good_len.to_le_bytes();
 
@@ -60,7 +114,10 @@ impl Labeller {
             package[BLOCK_BYTE_POINTER + 1]
         ]);
 
-        let flag_type = Flag::get_type(package[FLAG_BYTE_POINTER]);
+        let flag = package[FLAG_BYTE_POINTER];
+        let flag_type = Flag::get_type(flag & !(COMPRESSED | CHECKSUMMED));
+        let is_compressed = flag & COMPRESSED != 0;
+        let is_checksummed = flag & CHECKSUMMED != 0;
 
         let good_len = u32::from_le_bytes([
             package[GOOD_LEN_BYTE_POINTER],
@@ -72,7 +129,9 @@ impl Labeller {
         LabelInfo {
             block_count,
             flag_type,
-            good_len
+            good_len,
+            is_compressed,
+            is_checksummed
         }
     }
 }
@@ -90,7 +149,9 @@ mod tests {
             Labeller::label(&mut Vec::<u8>::new(), LabelInfo {
                 block_count: 0,
                 flag_type: FlagType::Insert,
-                good_len: 0
+                good_len: 0,
+                is_compressed: false,
+                is_checksummed: false
             })
         }
     }
@@ -108,8 +169,10 @@ mod tests {
         fn label_read(){
             let label_info = LabelInfo {
                 block_count: 1,
-                flag_type: FlagType::Insert,
-                good_len: 1
+                flag_type: FlagType::Delete,
+                good_len: 1,
+                is_compressed: true,
+                is_checksummed: true
             };
 
             let mut package: Vec<u8> = Vec::new();
@@ -123,6 +186,21 @@ mod tests {
             assert_eq!(label_info.block_count, read_label.block_count);
             assert_eq!(label_info.flag_type, read_label.flag_type);
             assert_eq!(label_info.good_len, read_label.good_len);
+            assert_eq!(label_info.is_compressed, read_label.is_compressed);
+            assert_eq!(label_info.is_checksummed, read_label.is_checksummed);
+
+            // anything changed after sealing is caught
+            package.resize(Labeller::get_header_size() + 1, 7);
+            Labeller::seal(&mut package);
+
+            assert!(Labeller::verify(&package));
+
+            for index in 0..package.len() {
+                let mut changed = package.clone();
+                changed[index] ^= 0x10;
+
+                assert!(!Labeller::verify(&changed));
+            }
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,205 @@
+/// Ledger is the write-ahead log of a godown. Every change to the
+/// data file is first written here as an entry holding all of its
+/// writes before any of them reach the data file. How soon an
+/// entry is synced is up to the durability the godown runs with.
+/// Entry = [body len u32][checksum u32][body]
+/// Body = one or more [pointer u64][len u32][bytes]
+/// An entry cut short by a crash fails its checksum and is dropped,
+/// so a change is either replayed whole on startup or never seen.
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write as IoWrite};
+use crate::checksum::Checksum;
+use fumble::Fumble;
+
+const TOTAL_LEN_BYTE: usize = 4;
+const TOTAL_CHECKSUM_BYTE: usize = 4;
+const TOTAL_POINTER_BYTE: usize = 8;
+const TOTAL_WRITE_LEN_BYTE: usize = 4;
+
+// Bytes to put at a pointer of the data file
+pub type Write = (usize, Vec<u8>);
+
+pub struct Ledger(File);
+
+impl Ledger {
+    pub fn new(file_path: &str) -> Self {
+        let file_result = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(file_path);
+
+        match file_result {
+            Ok(file) => Self(file),
+            Err(err) => panic!("Ledger {:?}", err)
+        }
+    }
+
+    pub fn get_len(&self) -> usize {
+        match self.0.metadata() {
+            Ok(metadata) => metadata.len() as usize,
+            Err(_) => 0
+        }
+    }
+
+    // Returns once the entry is on disk when synced,
+    // else it is left for a later sync to put there.
+    pub fn record(&mut self, writes: &[Write], is_synced: bool) -> Result<(), Fumble> {
+        let mut body: Vec<u8> = Vec::new();
+
+        for (pointer, bytes) in writes {
+            body.extend((*pointer as u64).to_le_bytes());
+            body.extend((bytes.len() as u32).to_le_bytes());
+            body.extend(bytes);
+        }
+
+        let mut entry: Vec<u8> = Vec::with_capacity(TOTAL_LEN_BYTE + TOTAL_CHECKSUM_BYTE + body.len());
+
+        entry.extend((body.len() as u32).to_le_bytes());
+        entry.extend(Checksum::compute(&[&body]).to_le_bytes());
+        entry.extend(body);
+
+        let end = self.get_len() as u64;
+
+        if self.0.seek(SeekFrom::Start(end)).is_err()
+            || self.0.write_all(&entry).is_err()
+            || (is_synced && self.0.sync_data().is_err()) {
+            // A part written is dropped on replay, but it
+            // would hide any entry written after it.
+            let _ = self.0.set_len(end);
+
+            return Err(Fumble::Disk);
+        }
+
+        Ok(())
+    }
+
+    // Writes of every whole entry, oldest first
+    pub fn read(&mut self) -> Vec<Vec<Write>> {
+        let mut bytes: Vec<u8> = Vec::new();
+        let mut entries: Vec<Vec<Write>> = Vec::new();
+        let mut pos = 0;
+
+        let _ = self.0.seek(SeekFrom::Start(0));
+
+        if self.0.read_to_end(&mut bytes).is_err() {
+            return entries;
+        }
+
+        while let Some((writes, len)) = Self::read_entry(&bytes[pos..]) {
+            entries.push(writes);
+            pos += len;
+        }
+
+        entries
+    }
+
+    // None when the entry is cut short or its checksum does not match
+    fn read_entry(bytes: &[u8]) -> Option<(Vec<Write>, usize)> {
+        let body_start = TOTAL_LEN_BYTE + TOTAL_CHECKSUM_BYTE;
+        let body_len = u32::from_le_bytes(bytes.get(0..TOTAL_LEN_BYTE)?.try_into().unwrap()) as usize;
+        let checksum = u32::from_le_bytes(bytes.get(TOTAL_LEN_BYTE..body_start)?.try_into().unwrap());
+        let body = bytes.get(body_start..(body_start + body_len))?;
+
+        if Checksum::compute(&[body]) != checksum {
+            return None;
+        }
+
+        let mut writes: Vec<Write> = Vec::new();
+        let mut pos = 0;
+
+        while pos < body_len {
+            let len_start = pos + TOTAL_POINTER_BYTE;
+            let bytes_start = len_start + TOTAL_WRITE_LEN_BYTE;
+            let pointer = u64::from_le_bytes(body.get(pos..len_start)?.try_into().unwrap()) as usize;
+            let len = u32::from_le_bytes(body.get(len_start..bytes_start)?.try_into().unwrap()) as usize;
+
+            writes.push((pointer, body.get(bytes_start..(bytes_start + len))?.to_vec()));
+            pos = bytes_start + len;
+        }
+
+        Some((writes, body_start + body_len))
+    }
+
+    pub fn sync(&mut self) -> Result<(), Fumble> {
+        match self.0.sync_data() {
+            Ok(_) => Ok(()),
+            Err(_) => Err(Fumble::Disk)
+        }
+    }
+
+    // Called once every entry has been synced to the data file
+    pub fn clear(&mut self) -> Result<(), Fumble> {
+        if self.0.set_len(0).is_err() || self.0.sync_data().is_err() {
+            Err(Fumble::Disk)
+        }else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use uuid::Uuid;
+
+    fn create_ledger() -> (Ledger, String) {
+        let mut path = env::temp_dir();
+        path.push(Uuid::new_v4().to_string());
+
+        let file_path = path.to_str().unwrap().to_owned();
+
+        File::create(&file_path).unwrap();
+
+        (Ledger::new(&file_path), file_path)
+    }
+
+    #[test]
+    fn record() {
+        let (mut ledger, file_path) = create_ledger();
+        let first: Vec<Write> = vec![(0, vec![1, 2, 3])];
+        let second: Vec<Write> = vec![(256, vec![4; 300]), (1024, vec![0; 256])];
+
+        ledger.record(&first, true).unwrap();
+        ledger.record(&second, false).unwrap();
+        ledger.sync().unwrap();
+
+        assert_eq!(Ledger::new(&file_path).read(), vec![first.clone(), second]);
+
+        ledger.clear().unwrap();
+
+        assert!(ledger.read().is_empty());
+
+        std::fs::remove_file(file_path).unwrap();
+    }
+
+    #[test]
+    fn torn() {
+        let (mut ledger, file_path) = create_ledger();
+        let first: Vec<Write> = vec![(512, vec![7; 100])];
+
+        ledger.record(&first, true).unwrap();
+        ledger.record(&[(768, vec![8; 100])], true).unwrap();
+
+        let len = ledger.get_len();
+
+        // Second entry cut short anywhere is rolled back
+        for cut in [len - 1, len - 50, len - 110] {
+            ledger.0.set_len(cut as u64).unwrap();
+
+            assert_eq!(ledger.read(), vec![first.clone()]);
+        }
+
+        // and so is one written over
+        ledger.record(&[(768, vec![8; 100])], true).unwrap();
+
+        let mut bytes = std::fs::read(&file_path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        std::fs::write(&file_path, bytes).unwrap();
+
+        assert_eq!(ledger.read().len(), 1);
+
+        std::fs::remove_file(file_path).unwrap();
+    }
+}
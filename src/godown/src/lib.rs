@@ -1,10 +1,11 @@
-/// DEFINITIONS:
-/// Pointer = Position of a byte
-/// Block = Series of bytes of fixed size
-/// Package = Block count + flag + good size + good + filler
-/// Good = Series of bytes
-/// Flag = Represent the status of Package (Inserted/Deleted)
-/// Filler = Extra null bytes
+//! DEFINITIONS:
+//! Pointer = Position of a byte
+//! Block = Series of bytes of fixed size
+//! Package = Block count + flag + good size + good + filler
+//! Good = Series of bytes
+//! Flag = Represent the status of Package (Inserted/Deleted)
+//! Filler = Extra null bytes
+//! Ledger = Write-ahead log every change goes through
 
 mod def;
 mod flag;
@@ -13,24 +14,41 @@ mod block;
 mod labeller;
 mod logistics;
 mod assembly_line;
+mod compressor;
+mod checksum;
+mod ledger;
 
+use std::time::{ Duration, Instant };
 use setup;
+use setup::env::Durability;
 use estate::Farming;
 use disk::Disk;
+use ledger::{ Ledger, Write };
 use flag::FlagType;
 use block::Block;
 use labeller::{LabelInfo, Labeller};
 use assembly_line::{AssemblyLine, Integrity};
-use def::{ GOOD_MAX_SIZE, CHUNK_SIZE, Package};
+use def::{ GOOD_MAX_SIZE, CHUNK_SIZE, LEDGER_MAX_SIZE, LEDGER_EXTENSION, HOLLOW_SHARE, Package};
 use fumble::Fumble;
 pub use logistics::Logistics;
 pub use logistics::Mode as LogisticsMode;
-pub use def::{Good, Address};
+pub use def::{Good, Address, Move};
 pub use def::Item;
 
 pub struct Godown {
     estate: Farming<Address>,
-    disk: Disk
+    disk: Disk,
+    ledger: Ledger,
+    compression: bool,
+    durability: Durability,
+    // Ledger entries not synced yet, and since when
+    pending: usize,
+    o_pending_since: Option<Instant>
+}
+
+// Ledger of a godown sits beside its data file
+pub fn ledger_file_name(location: &str) -> String {
+    format!("{}.{}", location, LEDGER_EXTENSION)
 }
 
 impl Godown {
@@ -42,19 +60,157 @@ impl Godown {
         } 
 
         let full_path = setup::file(location);
+        let ledger_path = setup::file(&ledger_file_name(location));
 
-        Self {
+        let mut godown = Self {
             estate: Farming::new(),
-            disk: Disk::new(&full_path)
-        }       
+            disk: Disk::new(&full_path),
+            ledger: Ledger::new(&ledger_path),
+            compression: setup::env::get_compression(),
+            durability: setup::env::get_durability(),
+            pending: 0,
+            o_pending_since: None
+        };
+
+        if let Err(err) = godown.replay() {
+            panic!("Ledger could not be replayed {:?}", err);
+        }
+
+        godown
+    }
+
+    // Puts every whole entry left in the ledger into the data
+    // file again. Writing an entry twice leaves the same bytes.
+    fn replay(&mut self) -> Result<(), Fumble> {
+        let entries = self.ledger.read();
+
+        if entries.is_empty() {
+            return self.ledger.clear();
+        }
+
+        println!("Replaying {} ledger entries", entries.len());
+
+        for writes in entries {
+            for (pointer, bytes) in writes {
+                self.disk.put(pointer, &bytes)?;
+            }
+        }
+
+        self.checkpoint()
+    }
+
+    fn checkpoint(&mut self) -> Result<(), Fumble> {
+        self.disk.sync()?;
+        self.ledger.clear()?;
+        self.pending = 0;
+        self.o_pending_since = None;
+
+        Ok(())
+    }
+
+    // Puts every committed change on disk
+    pub fn sync(&mut self) -> Result<(), Fumble> {
+        if self.pending > 0 {
+            self.ledger.sync()?;
+            self.pending = 0;
+            self.o_pending_since = None;
+        }
+
+        Ok(())
+    }
+
+    // Syncs once the group limits are reached, without a write
+    pub fn sync_due(&mut self) -> Result<(), Fumble> {
+        if self.is_sync_due() {
+            self.sync()
+        }else {
+            Ok(())
+        }
+    }
+
+    // Time left before unsynced writes are due under group durability,
+    // None when nothing is waiting on it
+    pub fn get_sync_wait(&self) -> Option<Duration> {
+        match (self.durability, self.o_pending_since) {
+            (Durability::Group(_, millis), Some(since)) => {
+                Some(Duration::from_millis(millis).saturating_sub(since.elapsed()))
+            },
+            _ => None
+        }
+    }
+
+    // Whether every committed change would outlast a power failure
+    pub fn is_durable(&self) -> bool {
+        self.pending == 0
+    }
+
+    // Group limits are checked as writes come in and by
+    // sync_due, which the owner calls while it waits.
+    fn is_sync_due(&self) -> bool {
+        match (self.durability, self.o_pending_since) {
+            (Durability::Group(writes, millis), Some(since)) => {
+                self.pending >= writes || since.elapsed() >= Duration::from_millis(millis)
+            },
+            _ => false
+        }
+    }
+
+    // All writes reach the data file, or none of them do. Once the
+    // entry is in the ledger the change stands, and a data file
+    // failing after that is only set right by a replay on restart.
+    // An entry of more than one write, as a replace or a compaction
+    // moving a good, is synced before its writes go out whatever the
+    // durability, else a power failure could keep only some of them
+    // and leave a good in two places. A lone write has no such half.
+    fn commit(&mut self, writes: Vec<Write>) -> Result<(), Fumble> {
+        let is_synced = self.durability == Durability::Write || writes.len() > 1;
+
+        self.ledger.record(&writes, is_synced)?;
+
+        // Syncing the ledger takes every entry before this one with it
+        if is_synced {
+            self.pending = 0;
+            self.o_pending_since = None;
+        }else {
+            self.pending += 1;
+            self.o_pending_since.get_or_insert_with(Instant::now);
+        }
+
+        for (pointer, bytes) in &writes {
+            if let Err(err) = self.disk.put(*pointer, bytes) {
+                panic!("Data file could not be written, restart to replay the ledger {:?}", err);
+            }
+        }
+
+        // Either is tried again on the next commit if it fails
+        if self.ledger.get_len() > LEDGER_MAX_SIZE {
+            let _ = self.checkpoint();
+        }else if self.is_sync_due() {
+            let _ = self.sync();
+        }
+
+        Ok(())
     }
 
     pub fn place(&mut self, good: Good) -> Result<Address, Fumble> {
+        let (address, writes) = self.pack(good)?;
+
+        if let Err(err) = self.commit(writes) {
+            self.estate.retain(address);
+
+            return Err(err);
+        }
+
+        Ok(address)
+    }
+
+    // Allots an address for the good and gives the writes putting it there
+    fn pack(&mut self, good: Good) -> Result<(Address, Vec<Write>), Fumble> {
         if good.len() > GOOD_MAX_SIZE {
             Err(Fumble::ScalarsCombinedSize)
         }else {
             // pack good
-            let package = AssemblyLine::assemble(good);
+            let package = AssemblyLine::assemble(good, self.compression);
 
             // get block count
             let label_info = Labeller::read(&package);
@@ -67,15 +223,52 @@ impl Godown {
 
             // convert location to pointer position
             let pointer = Block::get_pointer(location);
+            let mut writes: Vec<Write> = vec![(pointer, package)];
 
-            // put in disk
-            self.disk.put(pointer, &package);
+            writes.extend(self.label_retained(location + label_info.block_count as usize));
 
-            Ok(address)
+            Ok((address, writes))
         }
     }
 
-    pub fn remove(&mut self, address: Address){
+    // What is left of a retained estate a package was put in
+    // needs a header, else a scan on startup would find no
+    // package there. A header counts at most u16::MAX blocks.
+    fn label_retained(&self, mut location: usize) -> Vec<Write> {
+        let mut writes: Vec<Write> = Vec::new();
+        let mut size = self.estate.get_retained_size(location).unwrap_or(0);
+
+        while size > 0 {
+            let block_count = size.min(u16::MAX as usize);
+            let mut header: Package = vec![0; Labeller::get_header_size()];
+
+            Labeller::label(&mut header, LabelInfo {
+                block_count: block_count as u16,
+                flag_type: FlagType::Delete,
+                good_len: 0,
+                is_compressed: false,
+                is_checksummed: false
+            });
+
+            writes.push((Block::get_pointer(location), header));
+            location += block_count;
+            size -= block_count;
+        }
+
+        writes
+    }
+
+    pub fn remove(&mut self, address: Address) -> Result<(), Fumble> {
+        let write = self.unpack(address);
+
+        self.commit(vec![write])?;
+        self.estate.retain(address);
+
+        Ok(())
+    }
+
+    // Gives the write marking the package at address deleted
+    fn unpack(&mut self, address: Address) -> Write {
         // get location
         let location = self.estate.get_location(address).unwrap();
 
@@ -94,31 +287,137 @@ impl Godown {
         let label_info = LabelInfo {
             block_count: block_count as u16,
             flag_type: FlagType::Delete,
-            good_len: 0
+            good_len: 0,
+            is_compressed: false,
+            is_checksummed: false
         };
 
         // lanel the block count
         Labeller::label(&mut package, label_info);
 
-        self.disk.put(pointer, &package);
-
-        self.estate.retain(address);
+        (pointer, package)
     }    
 
+    // New package and removal of the old one go in one ledger
+    // entry, so a crash never leaves both or neither on disk.
     pub fn replace(&mut self, address: Address, good: Good) -> Result<Address, Fumble> {
-        let place_result = self.place(good);
+        let (replaced_address, mut writes) = self.pack(good)?;
 
-        if place_result.is_err() {
-            place_result
-        }else {
-            let replaced_address = place_result.ok().unwrap();
-            self.remove(address);
-            
-            Ok(replaced_address)
+        writes.push(self.unpack(address));
+
+        if let Err(err) = self.commit(writes) {
+            self.estate.retain(replaced_address);
+
+            return Err(err);
         }
+
+        self.estate.retain(address);
+
+        Ok(replaced_address)
     }
 
-    pub fn get(&mut self, address: Address) -> Good {
+    // Moves up to steps packages from the end of the file into holes
+    // before them, all in one ledger entry, then cuts the file short.
+    // Round stops at a package that is corrupted or not movable, as its
+    // owner could not be told where it went.
+    pub fn compact<F>(&mut self, steps: usize, movable: F) -> Result<Vec<Move>, Fumble>
+    where F: Fn(&Good) -> bool
+    {
+        let mut writes: Vec<Write> = Vec::new();
+        let mut moved: Vec<Move> = Vec::new();
+        self.estate.trim();
+
+        let mut o_source = self.estate.get_last();
+
+        while let Some(source) = o_source {
+            if moved.len() == steps {
+                break;
+            }
+
+            let location = self.estate.get_location(source).unwrap();
+            let size = self.estate.get_size(source).unwrap();
+
+            // A hole filled in this round is no place to move
+            // from, as the package is not on disk there yet.
+            if moved.iter().any(|(_, target, _)| *target == source) {
+                break;
+            }
+
+            let package = self.disk.read(Block::get_pointer(location), Block::get_total_bytes(size));
+
+            let good = match AssemblyLine::dismantle(&package) {
+                Integrity::Consistent(good) if movable(&good) => good,
+                _ => break
+            };
+
+            let target = match self.estate.allot_retained_below(size, location) {
+                Some(target) => target,
+                None => break
+            };
+
+            let target_location = self.estate.get_location(target).unwrap();
+
+            writes.push((Block::get_pointer(target_location), package));
+            writes.extend(self.label_retained(target_location + size));
+            writes.push(self.unpack(source));
+            moved.push((source, target, good));
+
+            // next one down, holes on the way go with the trim
+            o_source = self.estate.get_before(location);
+        }
+
+        if !writes.is_empty() {
+            if let Err(err) = self.commit(writes) {
+                for (_, target, _) in moved {
+                    self.estate.retain(target);
+                }
+
+                return Err(err);
+            }
+
+            for (source, _, _) in &moved {
+                self.estate.retain(*source);
+            }
+        }
+
+        // Tried again on the next compaction if it fails,
+        // and the moves still have to reach their owners.
+        if let Err(fumble) = self.shrink() {
+            println!("Data file could not be shrunk {}", fumble.unwrap());
+        }
+
+        Ok(moved)
+    }
+
+    // Whether enough of the file is holes for a compaction to be worth it
+    pub fn is_hollow(&self) -> bool {
+        let holes = self.estate.get_retained_total();
+
+        holes > 0 && holes * HOLLOW_SHARE >= self.estate.get_end()
+    }
+
+    // Cuts off the end of the file holding only removed packages. The
+    // ledger is emptied first, so no moved package is only in it then.
+    fn shrink(&mut self) -> Result<(), Fumble> {
+        let end = Block::get_pointer(self.estate.trim());
+
+        if self.disk.get_len() > end {
+            self.checkpoint()?;
+            self.disk.truncate(end)?;
+        }
+
+        Ok(())
+    }
+
+    // Where the good at address starts in the file, past its header
+    pub fn get_pointer(&self, address: Address) -> Option<usize> {
+        let location = self.estate.get_location(address)?;
+
+        Some(Block::get_pointer(location) + Labeller::get_header_size())
+    }
+
+    // Corrupted good is an error rather than a panic
+    pub fn get(&mut self, address: Address) -> Result<Good, Fumble> {
         let location = self.estate.get_location(address).unwrap();
 
         let pointer = Block::get_pointer(location);
@@ -128,7 +427,7 @@ impl Godown {
         let len = Block::get_total_bytes(size);
         let package = self.disk.read(pointer, len);
         
-        AssemblyLine::dismantle(&package).unwrap()
+        AssemblyLine::dismantle(&package).into_good()
     }
 
     pub fn transfer_chunk(&mut self, logistics: &mut Logistics) {
@@ -160,18 +459,29 @@ impl Godown {
         
             let package_len = Block::get_total_bytes(label_info.block_count as usize);
             
-            if counter + package_len < chunk_len {
-                let package: Package = chunk[counter..(counter + package_len)].to_vec();
+            // Package at the start of a chunk not fitting in it is
+            // larger than the chunk, so it gets a read of its own.
+            if counter + package_len < chunk_len || counter == 0 {
+                let package: Package = if counter + package_len < chunk_len {
+                    chunk[counter..(counter + package_len)].to_vec()
+                }else {
+                    self.disk.read(pointer, package_len)
+                };
                 
                 match logistics.get_mode() {
                     LogisticsMode::Uninitialize => {
-                        let integrity = AssemblyLine::dismantle(&package);
+                        // Removed package has no good to dismantle
+                        if label_info.flag_type != FlagType::Delete {
+                            let integrity = AssemblyLine::dismantle(&package);
 
-                        match integrity {
-                            Integrity::Consistent(good) => {
-                                logistics.load_item(Item::WithoutAddress(good))
-                            },
-                            _ => {}
+                            match integrity {
+                                Integrity::Consistent(good) => {
+                                    logistics.load_item(Item::WithoutAddress(good))
+                                },
+                                Integrity::Inconsistent => {
+                                    logistics.load_item(Item::Corrupted(None))
+                                }
+                            }
                         }
                     },
 
@@ -188,7 +498,7 @@ impl Godown {
                                     logistics.load_item(Item::WithAddress(address, good));
                                 },
                                 Integrity::Inconsistent => {
-                                    self.remove(address);
+                                    logistics.load_item(Item::Corrupted(Some(address)));
                                 }
                             }
                         }
@@ -209,6 +519,13 @@ impl Godown {
 
 }
 
+// Leaves an empty ledger behind on a clean close
+impl Drop for Godown {
+    fn drop(&mut self) {
+        let _ = self.checkpoint();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -222,6 +539,29 @@ mod tests {
         Uuid::new_v4().to_string()
     }
 
+    // A godown file in the temp folder, removed along with
+    // its ledger once dropped, even when an assertion fails.
+    struct TestFile(String);
+
+    impl TestFile {
+        fn new() -> Self {
+            setup::env::set_folder_path(env::temp_dir().to_str());
+
+            Self(create_file_path())
+        }
+
+        fn get_path(&self) -> &str {
+            &self.0
+        }
+    }
+
+    impl Drop for TestFile {
+        fn drop(&mut self) {
+            remove_file(&self.0);
+            remove_file(&ledger_file_name(&self.0));
+        }
+    }
+
     fn remove_file(file_path: &str) {
         let folder_path = env::var("FOLDER_PATH").unwrap();
         let mut path = PathBuf::from(folder_path);
@@ -303,4 +643,316 @@ mod tests {
             // }
         }
     }
-}
\ No newline at end of file
+
+    mod ledger {
+        use super::*;
+
+        pub fn load(file_path: &str) -> Vec<Good> {
+            let mut godown = Godown::new(file_path);
+            let mut logistics = Logistics::new(LogisticsMode::Initialize);
+            let mut goods: Vec<Good> = Vec::new();
+
+            while !logistics.get_has_done() {
+                godown.transfer_chunk(&mut logistics);
+
+                for item in logistics.unload() {
+                    if let Item::WithAddress(_, good) = item {
+                        goods.push(good);
+                    }
+                }
+            }
+
+            goods
+        }
+
+        #[test]
+        fn crashed_replace() {
+            for is_torn in [false, true] {
+                let test_file = TestFile::new();
+                let file_path = test_file.get_path();
+                let mut godown = Godown::new(file_path);
+                let old: Good = vec![1; 100];
+                let new: Good = vec![2; 300];
+                let address = godown.place(old.clone()).unwrap();
+
+                // Crash after the entry is logged, before the data file sees it
+                let (_, mut writes) = godown.pack(new.clone()).unwrap();
+
+                writes.push(godown.unpack(address));
+                godown.ledger.record(&writes, true).unwrap();
+                std::mem::forget(godown);
+
+                let ledger_path = setup::file(&ledger_file_name(file_path));
+
+                if is_torn {
+                    let len = fs::metadata(&ledger_path).unwrap().len();
+                    fs::OpenOptions::new().write(true).open(&ledger_path).unwrap().set_len(len - 1).unwrap();
+                }
+
+                let goods = load(file_path);
+
+                // replayed whole or rolled back, never both copies
+                if is_torn {
+                    assert_eq!(goods, vec![old]);
+                }else {
+                    assert_eq!(goods, vec![new]);
+                }
+            }
+        }
+    }
+
+    mod compact {
+        use super::*;
+
+        #[test]
+        fn moves_into_holes() {
+            let test_file = TestFile::new();
+            let file_path = test_file.get_path();
+            let mut godown = Godown::new(file_path);
+            let goods: Vec<Good> = (0..10).map(|index| vec![index as u8 + 1; 100]).collect();
+            let mut addresses: Vec<Address> = goods.iter().map(|good| godown.place(good.clone()).unwrap()).collect();
+
+            assert!(!godown.is_hollow());
+
+            // holes at the front, the tail left in use
+            for index in [0, 2, 4, 9] {
+                godown.remove(addresses[index]).unwrap();
+            }
+
+            assert!(godown.is_hollow());
+
+            let moves = godown.compact(2, |_| true).unwrap();
+
+            // 9 is trimmed, then 8 and 7 move down
+            assert_eq!(moves.len(), 2);
+            assert_eq!(moves[0].0, addresses[8]);
+            assert_eq!(moves[0].2, goods[8]);
+            assert_eq!(moves[1].0, addresses[7]);
+
+            for (source, target, _) in &moves {
+                let index = addresses.iter().position(|address| address == source).unwrap();
+                addresses[index] = *target;
+            }
+
+            assert_eq!(godown.disk.get_len(), Block::get_total_bytes(7));
+
+            // nothing is left to move into
+            assert!(godown.compact(10, |_| true).unwrap().len() == 1);
+            assert!(godown.compact(10, |_| true).unwrap().is_empty());
+            assert_eq!(godown.disk.get_len(), Block::get_total_bytes(6));
+            assert!(!godown.is_hollow());
+
+            let mut loaded = ledger::load(file_path);
+            let mut expected: Vec<Good> = [1, 3, 5, 6, 7, 8].iter().map(|index| goods[*index].clone()).collect();
+
+            loaded.sort();
+            expected.sort();
+
+            assert_eq!(loaded, expected);
+        }
+
+        #[test]
+        fn stops_at_corrupted() {
+            let test_file = TestFile::new();
+            let file_path = test_file.get_path();
+            let mut godown = Godown::new(file_path);
+            let first = godown.place(vec![1; 100]).unwrap();
+            let last = godown.place(vec![2; 100]).unwrap();
+
+            godown.remove(first).unwrap();
+
+            // not movable stays where it is
+            assert!(godown.compact(10, |good| good[0] != 2).unwrap().is_empty());
+
+            // and so does a corrupted one
+            let pointer = Block::get_pointer(godown.estate.get_location(last).unwrap());
+            godown.disk.put(pointer + Labeller::get_header_size(), &vec![3]).unwrap();
+
+            assert!(godown.compact(10, |_| true).unwrap().is_empty());
+            assert_eq!(godown.estate.get_location(last), Some(1));
+        }
+    }
+
+    mod corrupted {
+        use super::*;
+        use crate::def::BLOCK_SIZE;
+
+        #[test]
+        fn left_in_place() {
+            let test_file = TestFile::new();
+            let file_path = test_file.get_path();
+            let mut godown = Godown::new(file_path);
+            let good: Good = vec![1; 100];
+
+            godown.place(vec![2; 100]).unwrap();
+            godown.place(good.clone()).unwrap();
+            godown.disk.put(Labeller::get_header_size(), &vec![3]).unwrap();
+            drop(godown);
+
+            let bytes = fs::read(setup::file(file_path)).unwrap();
+            let mut godown = Godown::new(file_path);
+            let mut logistics = Logistics::new(LogisticsMode::Initialize);
+            let mut corrupted: Vec<Address> = Vec::new();
+            let mut goods: Vec<Good> = Vec::new();
+
+            while !logistics.get_has_done() {
+                godown.transfer_chunk(&mut logistics);
+
+                for item in logistics.unload() {
+                    match item {
+                        Item::WithAddress(_, good) => goods.push(good),
+                        Item::Corrupted(Some(address)) => corrupted.push(address),
+                        _ => panic!()
+                    }
+                }
+            }
+
+            assert_eq!(goods, vec![good]);
+            assert_eq!(corrupted.len(), 1);
+
+            // its estate is not handed out again
+            let address = godown.place(vec![4; 100]).unwrap();
+
+            assert_eq!(godown.estate.get_location(address), Some(2));
+            assert!(fs::read(setup::file(file_path)).unwrap()[..BLOCK_SIZE] == bytes[..BLOCK_SIZE]);
+        }
+    }
+
+    mod split {
+        use super::*;
+        use crate::def::BLOCK_SIZE;
+
+        #[test]
+        fn rest_is_labelled() {
+            let test_file = TestFile::new();
+            let file_path = test_file.get_path();
+            let mut godown = Godown::new(file_path);
+            // random, so it is not compressed below 4 blocks
+            let large: Good = (0..(BLOCK_SIZE * 3 + 100)).map(|_| rand::thread_rng().gen()).collect();
+            let small: Good = vec![7; 10];
+            let last: Good = vec![9; 10];
+            let address = godown.place(large).unwrap();
+
+            godown.place(last.clone()).unwrap();
+            godown.remove(address).unwrap();
+
+            // first block of the 4 is taken, the rest stays retained
+            let small_address = godown.place(small.clone()).unwrap();
+
+            assert_eq!(godown.estate.get_location(small_address), Some(0));
+            assert_eq!(godown.estate.get_retained_size(1), Some(3));
+
+            drop(godown);
+
+            // a scan steps over the rest to the last package
+            assert_eq!(ledger::load(file_path), vec![small, last]);
+
+            let mut godown = Godown::new(file_path);
+            let mut logistics = Logistics::new(LogisticsMode::Initialize);
+
+            while !logistics.get_has_done() {
+                godown.transfer_chunk(&mut logistics);
+                logistics.unload();
+            }
+
+            assert_eq!(godown.estate.get_retained_size(1), Some(3));
+        }
+    }
+
+    mod durability {
+        use super::*;
+
+        #[test]
+        fn group() {
+            let test_file = TestFile::new();
+            let file_path = test_file.get_path();
+            let mut godown = Godown::new(file_path);
+
+            godown.durability = Durability::Group(3, 60_000);
+            godown.place(vec![1; 10]).unwrap();
+            godown.place(vec![2; 10]).unwrap();
+
+            assert!(!godown.is_durable());
+            assert!(godown.get_sync_wait().unwrap() > Duration::ZERO);
+
+            // third write fills the group
+            godown.place(vec![3; 10]).unwrap();
+
+            assert!(godown.is_durable());
+            assert_eq!(godown.get_sync_wait(), None);
+
+            godown.place(vec![4; 10]).unwrap();
+            godown.sync_due().unwrap();
+
+            assert!(!godown.is_durable());
+
+            // group time runs out with no write to notice it
+            godown.durability = Durability::Group(3, 0);
+            godown.sync_due().unwrap();
+
+            assert!(godown.is_durable());
+
+            godown.durability = Durability::None;
+
+            let address = godown.place(vec![4; 10]).unwrap();
+
+            // nothing will sync it, so nothing to wait for
+            assert!(!godown.is_durable());
+            assert_eq!(godown.get_sync_wait(), None);
+
+            godown.sync().unwrap();
+
+            assert!(godown.is_durable());
+
+            godown.place(vec![6; 10]).unwrap();
+
+            // a replace is synced whatever the durability, and what came before with it
+            godown.replace(address, vec![7; 10]).unwrap();
+
+            assert!(godown.is_durable());
+
+            godown.durability = Durability::Write;
+            godown.place(vec![5; 10]).unwrap();
+
+            assert!(godown.is_durable());
+        }
+    }
+
+    mod large {
+        use super::*;
+
+        #[test]
+        fn spanning_chunks() {
+            let test_file = TestFile::new();
+            let file_path = test_file.get_path();
+            let mut godown = Godown::new(file_path);
+            let small: Good = vec![1; 100];
+            // random, so compressing leaves it spanning chunks
+            let large: Good = (0..(CHUNK_SIZE * 3)).map(|_| rand::thread_rng().gen()).collect();
+
+            godown.place(small.clone()).unwrap();
+            let address = godown.place(large.clone()).unwrap();
+            godown.place(small.clone()).unwrap();
+
+            assert_eq!(godown.get(address).unwrap(), large);
+            assert!(godown.place(vec![1; GOOD_MAX_SIZE + 1]).is_err());
+
+            // large one starts a chunk of its own while loading
+            let mut new_godown = Godown::new(file_path);
+            let mut logistics = Logistics::new(LogisticsMode::Initialize);
+            let mut goods: Vec<Good> = Vec::new();
+
+            while !logistics.get_has_done() {
+                new_godown.transfer_chunk(&mut logistics);
+
+                for item in logistics.unload() {
+                    if let Item::WithAddress(_, good) = item {
+                        goods.push(good);
+                    }
+                }
+            }
+
+            assert_eq!(goods, vec![small.clone(), large, small]);
+        }
+    }
+}
@@ -1,41 +1,202 @@
 mod serde;
 mod parser;
+use std::collections::HashMap;
+use std::time::Duration;
 use serde::Serde;
-use vendor::Vendor;
+use vendor::{ Vendor, DEFAULT_COLLECTION };
 use parser::{Parser, QueryType};
+use scalar::{ Scalar, Integer };
+use fumble::Fumble;
 
-pub struct Marketplace(Vendor);
+// Packages moved per collection each time the server is idle
+const COMPACT_STEPS: usize = 16;
+// Pause between those steps while holes are left
+const COMPACT_PAUSE_MILLIS: u64 = 10;
+
+// Every collection has its own vendor, queries go to
+// the one named in their fields or else the one in use.
+pub struct Marketplace {
+    vendors: HashMap<String, Vendor>,
+    current: String
+}
 
 impl Marketplace {
     pub fn new() -> Self {
-        Self(Vendor::new())
+        let mut vendors: HashMap<String, Vendor> = HashMap::new();
+
+        vendors.insert(DEFAULT_COLLECTION.to_owned(), Vendor::new(DEFAULT_COLLECTION));
+
+        for collection in setup::env::get_collections() {
+            let vendor = Vendor::new(&collection);
+
+            vendors.insert(collection, vendor);
+        }
+
+        Self {
+            vendors,
+            current: DEFAULT_COLLECTION.to_owned()
+        }
+    }
+
+    fn save_collections(&self) {
+        let mut collections: Vec<String> = self.vendors.keys().filter(|name| *name != DEFAULT_COLLECTION).cloned().collect();
+        collections.sort();
+
+        setup::env::set_collections(&collections);
+    }
+
+    fn manage(&mut self, query_type: &QueryType) -> Result<Vec<Vec<(String, Scalar)>>, Fumble> {
+        match query_type {
+            QueryType::Use(name) => {
+                if !self.vendors.contains_key(name) {
+                    return Err(Fumble::Collection);
+                }
+
+                self.current = name.clone();
+
+                Ok(vec![vec![(String::from("using"), Scalar::Text(name.clone()))]])
+            },
+            QueryType::CreateCollection(name) => {
+                let mut count = 0;
+
+                if !self.vendors.contains_key(name) {
+                    self.vendors.insert(name.clone(), Vendor::new(name));
+                    self.save_collections();
+                    count = 1;
+                }
+
+                Ok(vec![vec![(String::from("created"), Scalar::Integer(Integer::new(count)))]])
+            },
+            QueryType::DropCollection(name) => {
+                if name == DEFAULT_COLLECTION {
+                    return Err(Fumble::Collection);
+                }
+
+                let mut count = 0;
+
+                if let Some(vendor) = self.vendors.remove(name) {
+                    // listed no more before the files go, so a crash
+                    // in between leaves no collection half there
+                    self.save_collections();
+                    vendor.destroy();
+                    count = 1;
+
+                    if &self.current == name {
+                        self.current = DEFAULT_COLLECTION.to_owned();
+                    }
+                }
+
+                Ok(vec![vec![(String::from("dropped"), Scalar::Integer(Integer::new(count)))]])
+            },
+            _ => {
+                let mut names: Vec<&String> = self.vendors.keys().collect();
+                names.sort();
+
+                Ok(names.into_iter().map(|name| vec![
+                    (String::from("collection"), Scalar::Text(name.clone())),
+                    (String::from("current"), Scalar::Boolean(*name == self.current))
+                ]).collect())
+            }
+        }
+    }
+
+    // A write tells whether it would outlast a power failure
+    fn written(pair: (String, Scalar), is_durable: bool) -> String {
+        Serde::response(vec![vec![pair, (String::from("durable"), Scalar::Boolean(is_durable))]])
     }
 
     pub fn query(&mut self, bytes: &[u8]) -> String {
+        let response = self.run(bytes);
+
+        self.sync_due();
+
+        response
+    }
+
+    // Called while no query waits. Collections with holes enough are
+    // compacted a few steps at a time, so a query coming in is never
+    // held up for long. Gives how long until there is more to do,
+    // None when nothing is left until the next query.
+    pub fn idle(&mut self) -> Option<Duration> {
+        self.sync_due();
+
+        let mut o_wait: Option<Duration> = None;
+
+        for vendor in self.vendors.values_mut() {
+            if vendor.is_hollow() {
+                if let Err(fumble) = vendor.compact(COMPACT_STEPS) {
+                    println!("Compaction stopped {}", fumble.unwrap());
+                }
+            }
+
+            let o_compact_wait = if vendor.is_hollow() { Some(Duration::from_millis(COMPACT_PAUSE_MILLIS)) } else { None };
+
+            o_wait = [o_wait, o_compact_wait, vendor.get_sync_wait()].into_iter().flatten().min();
+        }
+
+        o_wait
+    }
+
+    // Writes left unsynced under group durability are synced once
+    // its limits pass, after a query or while waiting for one.
+    fn sync_due(&mut self) {
+        for vendor in self.vendors.values_mut() {
+            if let Err(fumble) = vendor.sync_due() {
+                println!("Sync failed {}", fumble.unwrap());
+            }
+        }
+    }
+
+    fn run(&mut self, bytes: &[u8]) -> String {
         let query = Parser::parse(bytes);
         
         match query {
             Err(err) => {
                 return err.unwrap();
             },
-            Ok(query_type) => {
+            Ok(mut query_type) => {
+                if let QueryType::Use(_) | QueryType::CreateCollection(_) | QueryType::DropCollection(_) | QueryType::ListCollections = &query_type[0] {
+                    return match self.manage(&query_type[0]) {
+                        Ok(data) => Serde::response(data),
+                        Err(fumble) => fumble.unwrap()
+                    };
+                }
+
+                let collection = match query_type.last() {
+                    Some(QueryType::Collection(name)) => name.clone(),
+                    _ => self.current.clone()
+                };
+
+                if let Some(QueryType::Collection(_)) = query_type.last() {
+                    query_type.pop();
+                }
+
+                let vendor = match self.vendors.get_mut(&collection) {
+                    Some(vendor) => vendor,
+                    None => return Fumble::Collection.unwrap()
+                };
+
                 match &query_type[0] {
                     QueryType::Delete => {
                         match &query_type[1] {
-                            QueryType::Match(condition) => {
-                                let result = self.0.delete(condition.0.clone());
-                                Serde::response(vec![vec![result]])
+                            QueryType::Match(conditions) => {
+                                let result = vendor.delete(conditions.clone());
+
+                                match result {
+                                    Ok(pair) => Self::written(pair, vendor.is_durable()),
+                                    Err(fumble) => fumble.unwrap()
+                                }
                             },
                             _ => panic!()
                         }
                     },
                     QueryType::Set(pairs) => {
                         match &query_type[1] {
-                            QueryType::Match(condition) => {
-                                let result = self.0.update(condition.0.clone(), Some(pairs.clone()));
+                            QueryType::Match(conditions) => {
+                                let result = vendor.update(conditions.clone(), Some(pairs.clone()));
         
                                 match result {
-                                    Ok(pair) => Serde::response(vec![vec![pair]]),
+                                    Ok(pair) => Self::written(pair, vendor.is_durable()),
                                     Err(fumble) => fumble.unwrap()
                                 }
                                 
@@ -45,23 +206,93 @@ impl Marketplace {
                     },
                     QueryType::Return(fields) => {
                         match &query_type[1] {
-                            QueryType::Match(condition) => {
-                                if fields.len() == 0{
-                                    let data = self.0.search(condition.0.clone(), None);
-                                    Serde::response(data)
+                            QueryType::Match(conditions) => {
+                                let result = if fields.is_empty() {
+                                    vendor.search(conditions.clone(), None)
                                 }else {
-                                    let data = self.0.search(condition.0.clone(), Some(fields.clone()));
-                                    Serde::response(data)
+                                    vendor.search(conditions.clone(), Some(fields.clone()))
+                                };
+
+                                match result {
+                                    Ok(data) => Serde::response(data),
+                                    Err(fumble) => fumble.unwrap()
                                 }
                             },
                             _ => panic!()
                         }
                     },
+                    QueryType::Declare(field, spec) => {
+                        let result = vendor.declare(field, *spec);
+
+                        match result {
+                            Ok(pair) => Serde::response(vec![vec![pair]]),
+                            Err(fumble) => fumble.unwrap()
+                        }
+                    },
+                    QueryType::Schema(field, rule) => {
+                        let result = vendor.set_rule(field, rule.clone());
+
+                        match result {
+                            Ok(pair) => Serde::response(vec![vec![pair]]),
+                            Err(fumble) => fumble.unwrap()
+                        }
+                    },
+                    QueryType::SchemaStrict(strict) => {
+                        Serde::response(vec![vec![vendor.set_strict(*strict)]])
+                    },
+                    QueryType::DropSchema => {
+                        Serde::response(vec![vec![vendor.drop_schema()]])
+                    },
+                    QueryType::ListSchema => {
+                        Serde::response(vendor.list_schema())
+                    },
+                    QueryType::RenameField(old, new) => {
+                        match vendor.rename_field(old, new) {
+                            Ok(pair) => Serde::response(vec![vec![pair]]),
+                            Err(fumble) => fumble.unwrap()
+                        }
+                    },
+                    QueryType::DropUnusedFields => {
+                        match vendor.drop_unused_fields() {
+                            Ok(data) => Serde::response(data),
+                            Err(fumble) => fumble.unwrap()
+                        }
+                    },
+                    QueryType::CreateIndex(fields, kind, filter) => {
+                        let result = vendor.create_index(fields, *kind, filter.clone());
+
+                        match result {
+                            Ok(pair) => Serde::response(vec![vec![pair]]),
+                            Err(fumble) => fumble.unwrap()
+                        }
+                    },
+                    QueryType::CreateExpiry(field, seconds) => {
+                        let result = vendor.create_expiry(field, *seconds);
+
+                        match result {
+                            Ok(pair) => Serde::response(vec![vec![pair]]),
+                            Err(fumble) => fumble.unwrap()
+                        }
+                    },
+                    QueryType::DropIndex(fields) => {
+                        let result = vendor.drop_index(fields);
+
+                        match result {
+                            Ok(pair) => Serde::response(vec![vec![pair]]),
+                            Err(fumble) => fumble.unwrap()
+                        }
+                    },
+                    QueryType::ListIndexes => {
+                        Serde::response(vendor.list_indexes())
+                    },
+                    QueryType::VerifyIndexes(repair) => {
+                        Serde::response(vendor.verify_indexes(*repair))
+                    },
                     QueryType::Create(pairs) => {
-                        let result = self.0.create(pairs.clone());
+                        let result = vendor.create(pairs.clone());
         
                         match result {
-                            Ok(pair) => Serde::response(vec![vec![pair]]),
+                            Ok(pair) => Self::written(pair, vendor.is_durable()),
                             Err(fumble) => fumble.unwrap()
                         }
                     },
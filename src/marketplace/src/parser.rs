@@ -1,6 +1,7 @@
 
-use scalar::Scalar;
+use scalar::{ Scalar, Spec, Rounding };
 use fumble::Fumble;
+use vendor::{ Operator, IndexKind, Rule, Allowed };
 
 const SMALL_A_TO_Z: [u8; 2] = [97, 122];
 const CAPTIAL_A_TO_Z: [u8; 2] = [65, 90];
@@ -8,7 +9,6 @@ const NUMERIC_0_TO_9: [u8; 2] = [48, 57];
 const UNDERSCORE: u8 = 95;
 // const SPACE: u8 = 32;
 // const NON_ASCII: u8 = 240;
-const EQUAL: u8 = 61;
 // const SINGLE_QUOTE: u8 = 39;
 // const BACKSLASH: u8 = 92;
 const QUERY_CREATE: [u8; 6] = [99, 114, 101, 97, 116, 101];
@@ -16,21 +16,75 @@ const QUERY_MATCH: [u8; 5] = [109, 97, 116, 99, 104];
 const QUERY_RETURN: [u8; 6] = [114, 101, 116, 117, 114, 110];
 const QUERY_DELETE: [u8; 6] = [100, 101, 108, 101, 116, 101];
 const QUERY_SET: [u8; 3] = [115, 101, 116];
+const QUERY_DECLARE: &[u8] = b"declare";
+const KEYWORD_DECIMAL: &[u8] = b"decimal";
+const QUERY_DROP: &[u8] = b"drop";
+const QUERY_LIST: &[u8] = b"list";
+const KEYWORD_INDEX: &[u8] = b"index";
+const KEYWORD_INDEXES: &[u8] = b"indexes";
+const KEYWORD_UNIQUE: &[u8] = b"unique";
+const KEYWORD_AND: &[u8] = b"and";
+const KEYWORD_TEXT: &[u8] = b"text";
+const KEYWORD_STEMMED: &[u8] = b"stemmed";
+const KEYWORD_SPARSE: &[u8] = b"sparse";
+const KEYWORD_WHERE: &[u8] = b"where";
+const QUERY_VERIFY: &[u8] = b"verify";
+const KEYWORD_REPAIR: &[u8] = b"repair";
+const KEYWORD_EXPIRY: &[u8] = b"expiry";
+const KEYWORD_AFTER: &[u8] = b"after";
+const QUERY_USE: &[u8] = b"use";
+const KEYWORD_COLLECTION: &[u8] = b"collection";
+const KEYWORD_COLLECTIONS: &[u8] = b"collections";
+const DOT: u8 = 46;
+const QUERY_SCHEMA: &[u8] = b"schema";
+const KEYWORD_STRICT: &[u8] = b"strict";
+const KEYWORD_LOOSE: &[u8] = b"loose";
+const KEYWORD_REQUIRED: &[u8] = b"required";
+const KEYWORD_MIN: &[u8] = b"min";
+const KEYWORD_MAX: &[u8] = b"max";
+const KEYWORD_UNUSED: &[u8] = b"unused";
+const KEYWORD_FIELDS: &[u8] = b"fields";
+const QUERY_RENAME: &[u8] = b"rename";
+const KEYWORD_FIELD: &[u8] = b"field";
+const SCHEMA_USAGE: &str = "'schema' should be followed by 'strict', 'loose' or a field, a type of any, boolean, text, integer, decimal or number, and optionally 'required', 'min' and 'max'";
 
 type Field = String;
 type Pair = (Field, Scalar);
 type Pairs = Vec<Pair>;
 type Fields = Vec<Field>;
-type Operator = String;
 type Condition = (Pair, Operator);
+type Conditions = Vec<Condition>;
 
 #[derive(Debug)]
 pub enum QueryType {
     Create(Pairs),
-    Match(Condition),
+    Match(Conditions),
     Return(Fields),
     Set(Pairs),
-    Delete
+    Delete,
+    Declare(Field, Spec),
+    CreateIndex(Fields, IndexKind, Conditions),
+    // Field holding a unix timestamp and seconds after which records expire
+    CreateExpiry(Field, u64),
+    DropIndex(Fields),
+    ListIndexes,
+    // Repairs indexes found inconsistent when true
+    VerifyIndexes(bool),
+    Use(String),
+    CreateCollection(String),
+    DropCollection(String),
+    ListCollections,
+    Schema(Field, Rule),
+    // Unknown fields are turned down when true
+    SchemaStrict(bool),
+    DropSchema,
+    ListSchema,
+    // Fields no record uses are taken out
+    DropUnusedFields,
+    // Old and new name of a field
+    RenameField(Field, Field),
+    // Collection the query is on, when its fields name one
+    Collection(String)
 }
 
 pub struct Parser;
@@ -50,6 +104,9 @@ impl Parser {
             || (counter != pos && byte >= CAPTIAL_A_TO_Z[0] && byte <= CAPTIAL_A_TO_Z[1])
             || byte == UNDERSCORE {
                 field.push(byte as char)
+            }else if byte == DOT && counter != pos && !field.contains('.') {
+                // collection in front of field, e.g. users.name
+                field.push(byte as char)
             }else if byte == 0 {
                 break;
             }else {
@@ -60,75 +117,158 @@ impl Parser {
             counter += 1;
         }
 
-        if has_fumbled {
+        if has_fumbled || field.ends_with('.') {
             Err(Fumble::FieldName)
         }else {
             Ok((field, counter + 1))
         }
     }
 
-    fn parse_value(bytes: &[u8], pos: usize) -> Result<(Scalar, usize), Fumble> {
+    // Reads bytes till the next null byte
+    // Reads nothing past the end of bytes.
+    fn parse_token(bytes: &[u8], pos: usize) -> (&[u8], usize) {
         let len = bytes.len();
-        let mut counter = pos;
-        
-        while counter < len {
-            if bytes[counter] == 0 {
-                break;
-            }
+        let start = pos.min(len);
+        let mut counter = start;
 
+        while counter < len && bytes[counter] != 0 {
             counter += 1;
         }
 
-        let result = Scalar::from_type_string(&bytes[pos..counter]);
+        (&bytes[start..counter], counter + 1)
+    }
 
-        match result {
-            Ok(parsed) => Ok((parsed, counter + 1)),
-            Err(err) => Err(err)
+    fn has_keyword(bytes: &[u8], pos: usize, keyword: &[u8]) -> bool {
+        let (token, next) = Self::parse_token(bytes, pos);
+
+        token == keyword && next <= bytes.len()
+    }
+
+    // declare <field> decimal <precision> <scale> [half_even|half_up|truncate]
+    fn parse_declare(bytes: &[u8], pos: usize) -> Result<QueryType, Fumble> {
+        let field_result = Self::parse_field(bytes, pos);
+
+        match field_result {
+            Err(err) => Err(err),
+            Ok((field, pos)) => {
+                if field.is_empty() || !Self::has_keyword(bytes, pos, KEYWORD_DECIMAL) {
+                    return Err(Fumble::Declaration);
+                }
+
+                let (precision, pos) = Self::parse_token(bytes, pos + KEYWORD_DECIMAL.len() + 1);
+                let (scale, pos) = Self::parse_token(bytes, pos);
+                let (rounding, _) = Self::parse_token(bytes, pos);
+                let o_precision = String::from_utf8_lossy(precision).parse::<u8>().ok();
+                let o_scale = String::from_utf8_lossy(scale).parse::<u8>().ok();
+                let o_rounding = if rounding.is_empty() {
+                    Some(Rounding::HalfEven)
+                }else {
+                    Rounding::from_bytes(rounding)
+                };
+
+                match (o_precision, o_scale, o_rounding) {
+                    (Some(precision), Some(scale), Some(rounding)) => {
+                        match Spec::new(precision, scale, rounding) {
+                            Ok(spec) => Ok(QueryType::Declare(field, spec)),
+                            Err(_) => Err(Fumble::Declaration)
+                        }
+                    },
+                    _ => Err(Fumble::Declaration)
+                }
+            }
         }
     }
 
-    fn parse_operator(bytes: &[u8], pos: usize) -> Result<(String, usize), Fumble> {
-        let len = bytes.len();
-        let mut counter = pos;
+    // index <field> [<field>...] [where <condition> [and <condition>...]]
+    fn parse_index(bytes: &[u8], pos: usize) -> Result<(Fields, Conditions), Fumble> {
+        if !Self::has_keyword(bytes, pos, KEYWORD_INDEX) {
+            return Err(Fumble::Index);
+        }
 
-        while counter < len {
-            if bytes[counter] == 0 {
-                break
+        let mut fields: Fields = Vec::new();
+        let mut filter: Conditions = Vec::new();
+        let mut counter = pos + KEYWORD_INDEX.len() + 1;
+
+        while counter < bytes.len() {
+            if !fields.is_empty() && Self::has_keyword(bytes, counter, KEYWORD_WHERE) {
+                match Self::parse_condition(bytes, counter + KEYWORD_WHERE.len() + 1) {
+                    Ok((QueryType::Match(conditions), pos)) if pos >= bytes.len() => {
+                        filter = conditions;
+                        break;
+                    },
+                    _ => return Err(Fumble::Index)
+                }
             }
 
-            counter += 1;
+            match Self::parse_field(bytes, counter) {
+                Ok((field, pos)) if !field.is_empty() => {
+                    fields.push(field);
+                    counter = pos;
+                },
+                _ => return Err(Fumble::Index)
+            }
         }
-        
-        if counter - pos > 1 {
-            Err(Fumble::Operator)
-        }else if bytes[counter - 1] == EQUAL {
-            Ok((String::from("="), counter + 1))
+
+        if fields.is_empty() {
+            Err(Fumble::Index)
         }else {
-            Err(Fumble::Operator)
+            Ok((fields, filter))
         }
     }
 
+    fn parse_value(bytes: &[u8], pos: usize) -> Result<(Scalar, usize), Fumble> {
+        let (token, next) = Self::parse_token(bytes, pos);
+        let result = Scalar::from_type_string(token);
+
+        match result {
+            Ok(parsed) => Ok((parsed, next)),
+            Err(err) => Err(err)
+        }
+    }
+
+    fn parse_operator(bytes: &[u8], pos: usize) -> Result<(Operator, usize), Fumble> {
+        let (token, next) = Self::parse_token(bytes, pos);
+
+        match Operator::from_bytes(token) {
+            Some(operator) => Ok((operator, next)),
+            None => Err(Fumble::Operator)
+        }
+    }
+
+    // <field> <operator> <value> [and <field> <operator> <value>...]
     fn parse_condition(bytes: &[u8], pos: usize) -> Result<(QueryType, usize), Fumble> {
-        let field_result = Self::parse_field(bytes, pos);
+        let mut conditions: Conditions = Vec::new();
+        let mut counter = pos;
 
-        match field_result {
-            Err(err) => Err(err),
-            Ok((field, pos)) => {
-                let operator_result = Self::parse_operator(bytes, pos);
-
-                match operator_result {
-                    Err(err) => Err(err),
-                    Ok((operator, pos)) => {
-                        let scalar_result = Self::parse_value(bytes, pos);
-
-                        match scalar_result {
-                            Err(err) => Err(err),
-                            Ok((scalar, pos)) => {
-                                return Ok((QueryType::Match(((field, scalar), operator)), pos));
-                            }
-                        }
-                    }
-                }
+        loop {
+            let field_result = Self::parse_field(bytes, counter);
+
+            if field_result.is_err() {
+                return Err(field_result.err().unwrap());
+            }
+
+            let (field, pos) = field_result.ok().unwrap();
+            let operator_result = Self::parse_operator(bytes, pos);
+
+            if operator_result.is_err() {
+                return Err(operator_result.err().unwrap());
+            }
+
+            let (operator, pos) = operator_result.ok().unwrap();
+            let scalar_result = Self::parse_value(bytes, pos);
+
+            if scalar_result.is_err() {
+                return Err(scalar_result.err().unwrap());
+            }
+
+            let (scalar, pos) = scalar_result.ok().unwrap();
+
+            conditions.push(((field, scalar), operator));
+
+            if Self::has_keyword(bytes, pos, KEYWORD_AND) {
+                counter = pos + KEYWORD_AND.len() + 1;
+            }else {
+                return Ok((QueryType::Match(conditions), pos));
             }
         }
     }
@@ -188,26 +328,11 @@ impl Parser {
     }
 
     fn parse_action(bytes: &[u8], pos: usize) -> Result<QueryType, Fumble> {
-        if bytes[pos] == QUERY_DELETE[0] 
-        && bytes[pos + 1] == QUERY_DELETE[1]
-        && bytes[pos + 2] == QUERY_DELETE[2]
-        && bytes[pos + 3] == QUERY_DELETE[3]
-        && bytes[pos + 4] == QUERY_DELETE[4]
-        && bytes[pos + 5] == QUERY_DELETE[5]
-        && bytes[pos + 6] == 0 {
+        if Self::has_keyword(bytes, pos, &QUERY_DELETE) {
             Ok(QueryType::Delete)
-        }else if bytes[pos] == QUERY_RETURN[0] 
-        && bytes[pos + 1] == QUERY_RETURN[1]
-        && bytes[pos + 2] == QUERY_RETURN[2]
-        && bytes[pos + 3] == QUERY_RETURN[3]
-        && bytes[pos + 4] == QUERY_RETURN[4]
-        && bytes[pos + 5] == QUERY_RETURN[5]
-        && bytes[pos + 6] == 0 {
+        }else if Self::has_keyword(bytes, pos, &QUERY_RETURN) {
             Ok(QueryType::Return(Self::parse_fields(bytes, pos + 7)))
-        }else if bytes[pos] == QUERY_SET[0] 
-        && bytes[pos + 1] == QUERY_SET[1]
-        && bytes[pos + 2] == QUERY_SET[2]
-        && bytes[pos + 3] == 0 {
+        }else if Self::has_keyword(bytes, pos, &QUERY_SET) {
             let pairs_result = Self::parse_pairs(bytes, pos + 4);
 
             match pairs_result {
@@ -221,9 +346,241 @@ impl Parser {
         }
     }
 
+    // expiry index <field> [after <seconds>]
+    fn parse_expiry(bytes: &[u8], pos: usize) -> Result<(Field, u64), Fumble> {
+        if !Self::has_keyword(bytes, pos, KEYWORD_INDEX) {
+            return Err(Fumble::Index);
+        }
+
+        match Self::parse_field(bytes, pos + KEYWORD_INDEX.len() + 1) {
+            Ok((field, pos)) if !field.is_empty() => {
+                if pos >= bytes.len() {
+                    return Ok((field, 0));
+                }else if !Self::has_keyword(bytes, pos, KEYWORD_AFTER) {
+                    return Err(Fumble::Index);
+                }
+
+                match Self::parse_value(bytes, pos + KEYWORD_AFTER.len() + 1) {
+                    Ok((scalar @ Scalar::Integer(_), pos)) if pos >= bytes.len() && scalar.unwrap_integer() >= 0 => {
+                        Ok((field, scalar.unwrap_integer() as u64))
+                    },
+                    _ => Err(Fumble::Index)
+                }
+            },
+            _ => Err(Fumble::Index)
+        }
+    }
+
+    // rename field <old> <new>
+    fn parse_rename(bytes: &[u8], pos: usize) -> Result<QueryType, Fumble> {
+        if !Self::has_keyword(bytes, pos, KEYWORD_FIELD) {
+            return Err(Fumble::Rename);
+        }
+
+        let (old, pos) = Self::parse_field(bytes, pos + KEYWORD_FIELD.len() + 1)?;
+        let (new, pos) = Self::parse_field(bytes, pos)?;
+
+        if old.is_empty() || new.is_empty() || pos < bytes.len() {
+            Err(Fumble::Rename)
+        }else {
+            Ok(QueryType::RenameField(old, new))
+        }
+    }
+
+    // [sparse] [unique|text|stemmed text] before index, plain if none.
+    // Sparse leaves out records missing any of the fields,
+    // the same as a filter on every field being there.
+    fn parse_kind(bytes: &[u8], pos: usize) -> (IndexKind, usize) {
+        if Self::has_keyword(bytes, pos, KEYWORD_UNIQUE) {
+            (IndexKind::Unique, pos + KEYWORD_UNIQUE.len() + 1)
+        }else if Self::has_keyword(bytes, pos, KEYWORD_TEXT) {
+            (IndexKind::FullText(false), pos + KEYWORD_TEXT.len() + 1)
+        }else if Self::has_keyword(bytes, pos, KEYWORD_STEMMED) && Self::has_keyword(bytes, pos + KEYWORD_STEMMED.len() + 1, KEYWORD_TEXT) {
+            (IndexKind::FullText(true), pos + KEYWORD_STEMMED.len() + KEYWORD_TEXT.len() + 2)
+        }else {
+            (IndexKind::Plain, pos)
+        }
+    }
+
+    // schema strict|loose, or
+    // schema <field> <type> [required] [min <value>] [max <value>]
+    fn parse_schema(bytes: &[u8], pos: usize) -> Result<QueryType, Fumble> {
+        let usage = || Fumble::Schema(SCHEMA_USAGE.to_owned());
+
+        for (keyword, strict) in [(KEYWORD_STRICT, true), (KEYWORD_LOOSE, false)] {
+            if Self::has_keyword(bytes, pos, keyword) && pos + keyword.len() + 1 >= bytes.len() {
+                return Ok(QueryType::SchemaStrict(strict));
+            }
+        }
+
+        let (field, pos) = match Self::parse_field(bytes, pos) {
+            Ok((field, pos)) if !field.is_empty() => (field, pos),
+            _ => return Err(usage())
+        };
+        let (token, mut counter) = Self::parse_token(bytes, pos);
+        let allowed = match Allowed::from_bytes(token) {
+            Some(allowed) => allowed,
+            None => return Err(usage())
+        };
+        let mut rule = Rule { allowed, required: false, o_min: None, o_max: None };
+
+        if Self::has_keyword(bytes, counter, KEYWORD_REQUIRED) {
+            rule.required = true;
+            counter += KEYWORD_REQUIRED.len() + 1;
+        }
+
+        if Self::has_keyword(bytes, counter, KEYWORD_MIN) {
+            let (scalar, pos) = Self::parse_value(bytes, counter + KEYWORD_MIN.len() + 1).map_err(|_| usage())?;
+
+            rule.o_min = Some(scalar);
+            counter = pos;
+        }
+
+        if Self::has_keyword(bytes, counter, KEYWORD_MAX) {
+            let (scalar, pos) = Self::parse_value(bytes, counter + KEYWORD_MAX.len() + 1).map_err(|_| usage())?;
+
+            rule.o_max = Some(scalar);
+            counter = pos;
+        }
+
+        if counter < bytes.len() {
+            return Err(usage());
+        }
+
+        Ok(QueryType::Schema(field, rule))
+    }
+
+    // Name of a collection, which nothing may follow
+    fn parse_collection(bytes: &[u8], pos: usize) -> Result<String, Fumble> {
+        match Self::parse_field(bytes, pos) {
+            Ok((name, pos)) if !name.is_empty() && !name.contains('.') && pos >= bytes.len() => Ok(name),
+            _ => Err(Fumble::Collection)
+        }
+    }
+
+    // Takes the collection off a field, it has to be
+    // the same one as on any other field of the query.
+    fn strip_collection(field: &mut Field, o_collection: &mut Option<String>) -> Result<(), Fumble> {
+        if let Some((collection, literal)) = field.clone().split_once('.') {
+            if o_collection.as_ref().is_some_and(|name| name != collection) {
+                return Err(Fumble::Collection);
+            }
+
+            *o_collection = Some(collection.to_owned());
+            *field = literal.to_owned();
+        }
+
+        Ok(())
+    }
+
+    fn take_collection(query_types: &mut Vec<QueryType>) -> Result<(), Fumble> {
+        let mut o_collection: Option<String> = None;
+
+        for query_type in query_types.iter_mut() {
+            let mut fields: Vec<&mut Field> = Vec::new();
+
+            match query_type {
+                QueryType::Create(pairs) | QueryType::Set(pairs) => fields.extend(pairs.iter_mut().map(|pair| &mut pair.0)),
+                QueryType::Match(conditions) => fields.extend(conditions.iter_mut().map(|condition| &mut condition.0.0)),
+                QueryType::Return(literals) | QueryType::DropIndex(literals) => fields.extend(literals.iter_mut()),
+                QueryType::Declare(field, _) | QueryType::CreateExpiry(field, _) | QueryType::Schema(field, _) => fields.push(field),
+                QueryType::RenameField(old, new) => fields.extend([old, new]),
+                QueryType::CreateIndex(literals, _, filter) => {
+                    fields.extend(literals.iter_mut());
+                    fields.extend(filter.iter_mut().map(|condition| &mut condition.0.0));
+                },
+                _ => {}
+            }
+
+            for field in fields {
+                Self::strip_collection(field, &mut o_collection)?;
+            }
+        }
+
+        if let Some(collection) = o_collection {
+            query_types.push(QueryType::Collection(collection));
+        }
+
+        Ok(())
+    }
+
+    // use <collection>, create|drop collection <collection>,
+    // list collections or a query on a collection.
     pub fn parse(bytes: &[u8]) -> Result<Vec<QueryType>, Fumble> {
+        if Self::has_keyword(bytes, 0, QUERY_USE) {
+            return Self::parse_collection(bytes, QUERY_USE.len() + 1).map(|name| vec![QueryType::Use(name)]);
+        }
+
+        // a record with a field named collection is still a create
+        for (query, is_create) in [(&QUERY_CREATE[..], true), (QUERY_DROP, false)] {
+            if Self::has_keyword(bytes, 0, query) && Self::has_keyword(bytes, query.len() + 1, KEYWORD_COLLECTION) {
+                let pos = query.len() + KEYWORD_COLLECTION.len() + 2;
+
+                match Self::parse_collection(bytes, pos) {
+                    Ok(name) if is_create => return Ok(vec![QueryType::CreateCollection(name)]),
+                    Ok(name) => return Ok(vec![QueryType::DropCollection(name)]),
+                    Err(err) if !is_create => return Err(err),
+                    Err(_) => {}
+                }
+            }
+        }
+
+        if Self::has_keyword(bytes, 0, QUERY_LIST) && Self::has_keyword(bytes, QUERY_LIST.len() + 1, KEYWORD_COLLECTIONS) {
+            return Ok(vec![QueryType::ListCollections]);
+        }
+
+        for (query, query_type) in [(QUERY_LIST, QueryType::ListSchema), (QUERY_DROP, QueryType::DropSchema)] {
+            if Self::has_keyword(bytes, 0, query) && Self::has_keyword(bytes, query.len() + 1, QUERY_SCHEMA) {
+                return Ok(vec![query_type]);
+            }
+        }
+
+        let pos = QUERY_DROP.len() + KEYWORD_UNUSED.len() + 2;
+
+        if Self::has_keyword(bytes, 0, QUERY_DROP) && Self::has_keyword(bytes, QUERY_DROP.len() + 1, KEYWORD_UNUSED) && Self::has_keyword(bytes, pos, KEYWORD_FIELDS) {
+            return Ok(vec![QueryType::DropUnusedFields]);
+        }
+
+        let mut result = Self::parse_query(bytes)?;
+
+        Self::take_collection(&mut result)?;
+
+        Ok(result)
+    }
+
+    fn parse_query(bytes: &[u8]) -> Result<Vec<QueryType>, Fumble> {
         let mut result: Vec<QueryType> = Vec::new();
         
+        // A create can't have a field right after index,
+        // as values always start with a type code.
+        if Self::has_keyword(bytes, 0, &QUERY_CREATE) {
+            let pos = QUERY_CREATE.len() + 1;
+
+            if Self::has_keyword(bytes, pos, KEYWORD_EXPIRY) {
+                if let Ok((field, seconds)) = Self::parse_expiry(bytes, pos + KEYWORD_EXPIRY.len() + 1) {
+                    result.push(QueryType::CreateExpiry(field, seconds));
+
+                    return Ok(result);
+                }
+            }
+
+            let is_sparse = Self::has_keyword(bytes, pos, KEYWORD_SPARSE);
+            let pos = if is_sparse { pos + KEYWORD_SPARSE.len() + 1 } else { pos };
+            let (kind, pos) = Self::parse_kind(bytes, pos);
+
+            if let Ok((fields, mut filter)) = Self::parse_index(bytes, pos) {
+                if is_sparse {
+                    for field in &fields {
+                        filter.push(((field.clone(), Scalar::Boolean(true)), Operator::Exists));
+                    }
+                }
+
+                result.push(QueryType::CreateIndex(fields, kind, filter));
+
+                return Ok(result);
+            }
+        }
+
         if bytes[0] == QUERY_MATCH[0] 
         && bytes[1] == QUERY_MATCH[1]
         && bytes[2] == QUERY_MATCH[2]
@@ -267,6 +624,48 @@ impl Parser {
                     result.push(QueryType::Create(pairs));
                 }
             }
+        }else if Self::has_keyword(bytes, 0, QUERY_DECLARE) {
+            let declare_result = Self::parse_declare(bytes, QUERY_DECLARE.len() + 1);
+
+            match declare_result {
+                Err(err) => {
+                    return Err(err)
+                },
+                Ok(query_type) => {
+                    result.push(query_type);
+                }
+            }
+        }else if Self::has_keyword(bytes, 0, QUERY_DROP) {
+            let index_result = Self::parse_index(bytes, QUERY_DROP.len() + 1);
+
+            match index_result {
+                Err(err) => {
+                    return Err(err)
+                },
+                // An index is dropped by its fields alone
+                Ok((_, filter)) if !filter.is_empty() => {
+                    return Err(Fumble::Index)
+                },
+                Ok((fields, _)) => {
+                    result.push(QueryType::DropIndex(fields));
+                }
+            }
+        }else if Self::has_keyword(bytes, 0, QUERY_LIST) && Self::has_keyword(bytes, QUERY_LIST.len() + 1, KEYWORD_INDEXES) {
+            result.push(QueryType::ListIndexes);
+        }else if Self::has_keyword(bytes, 0, QUERY_SCHEMA) {
+            result.push(Self::parse_schema(bytes, QUERY_SCHEMA.len() + 1)?);
+        }else if Self::has_keyword(bytes, 0, QUERY_RENAME) {
+            result.push(Self::parse_rename(bytes, QUERY_RENAME.len() + 1)?);
+        }else if Self::has_keyword(bytes, 0, QUERY_VERIFY) && Self::has_keyword(bytes, QUERY_VERIFY.len() + 1, KEYWORD_INDEXES) {
+            let pos = QUERY_VERIFY.len() + KEYWORD_INDEXES.len() + 2;
+            let is_repair = Self::has_keyword(bytes, pos, KEYWORD_REPAIR);
+
+            // nothing else may follow
+            if !is_repair && pos < bytes.len() {
+                return Err(Fumble::Start)
+            }
+
+            result.push(QueryType::VerifyIndexes(is_repair));
         }else {
             return Err(Fumble::Start)
         }
@@ -315,9 +714,24 @@ mod tests {
         match operator_result {
             Err(err) => panic!("{:?}", err),
             Ok((operator, pos)) => {
-                assert_eq!(operator, "=".to_owned());
+                assert_eq!(operator, Operator::Equal);
+            }
+        }
+
+        let query = b"price\0>=\0&10\0";
+        let operator_result = Parser::parse_operator(query, 6);
+
+        match operator_result {
+            Err(err) => panic!("{:?}", err),
+            Ok((operator, pos)) => {
+                assert_eq!(operator, Operator::GreaterEqual);
+                assert_eq!(pos, 9);
             }
         }
+
+        let query = b"price\0=>\0&10\0";
+        
+        assert!(Parser::parse_operator(query, 6).is_err());
     }
 
     #[test]
@@ -339,6 +753,37 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_declare() {
+        let query = b"declare\0price\0decimal\x0010\x002\0half_up\0";
+        let query_type_result = Parser::parse(query);
+
+        match query_type_result {
+            Err(err) => panic!("{:?}", err),
+            Ok(query_type) => {
+                match &query_type[0] {
+                    QueryType::Declare(field, spec) => {
+                        assert_eq!(field, "price");
+                        assert_eq!(spec, &Spec::new(10, 2, Rounding::HalfUp).unwrap());
+                    },
+                    _ => panic!("Expected declare")
+                }
+            }
+        }
+
+        // rounding defaults to half even
+        let query = b"declare\0price\0decimal\x0010\x002\0";
+
+        match &Parser::parse(query).unwrap()[0] {
+            QueryType::Declare(_, spec) => assert_eq!(spec.get_rounding(), Rounding::HalfEven),
+            _ => panic!("Expected declare")
+        }
+
+        assert!(Parser::parse(b"declare\0price\0decimal\x002\x003\0").is_err());
+        assert!(Parser::parse(b"declare\0price\0text\x0010\x002\0").is_err());
+        assert!(Parser::parse(b"declare\0price\0decimal\x0010\x002\0ceil\0").is_err());
+    }
+
     #[test]
     fn parse() {
         let query = b"create\0field1\0#name\0field2\0!true\0";
@@ -349,4 +794,183 @@ mod tests {
             Ok(queryType) => println!("{:?}", queryType)
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn parse_index() {
+        match &Parser::parse(b"create\0index\0price\0").unwrap()[0] {
+            QueryType::CreateIndex(fields, kind, _) => {
+                assert_eq!(fields, &vec!["price".to_owned()]);
+                assert_eq!(kind, &IndexKind::Plain);
+            },
+            _ => panic!("Expected create index")
+        }
+
+        match &Parser::parse(b"create\0unique\0index\0email\0").unwrap()[0] {
+            QueryType::CreateIndex(fields, kind, _) => {
+                assert_eq!(fields, &vec!["email".to_owned()]);
+                assert_eq!(kind, &IndexKind::Unique);
+            },
+            _ => panic!("Expected create unique index")
+        }
+
+        match &Parser::parse(b"drop\0index\0price\0").unwrap()[0] {
+            QueryType::DropIndex(fields) => assert_eq!(fields, &vec!["price".to_owned()]),
+            _ => panic!("Expected drop index")
+        }
+
+        match &Parser::parse(b"list\0indexes\0").unwrap()[0] {
+            QueryType::ListIndexes => {},
+            _ => panic!("Expected list indexes")
+        }
+
+        // a field named index is still a create
+        match &Parser::parse(b"create\0index\0#book\0").unwrap()[0] {
+            QueryType::Create(pairs) => assert_eq!(pairs[0].0, "index"),
+            _ => panic!("Expected create")
+        }
+
+        match &Parser::parse(b"create\0index\0tenant\0created\0").unwrap()[0] {
+            QueryType::CreateIndex(fields, _, _) => assert_eq!(fields, &vec!["tenant".to_owned(), "created".to_owned()]),
+            _ => panic!("Expected create compound index")
+        }
+
+        match &Parser::parse(b"create\0stemmed\0text\0index\0description\0").unwrap()[0] {
+            QueryType::CreateIndex(_, kind, _) => assert_eq!(kind, &IndexKind::FullText(true)),
+            _ => panic!("Expected create text index")
+        }
+
+        match &Parser::parse(b"create\0text\0index\0description\0").unwrap()[0] {
+            QueryType::CreateIndex(_, kind, _) => assert_eq!(kind, &IndexKind::FullText(false)),
+            _ => panic!("Expected create text index")
+        }
+
+        match &Parser::parse(b"create\0sparse\0index\0tenant\0created\0where\0archived_at\0exists\0!false\0").unwrap()[0] {
+            QueryType::CreateIndex(fields, _, filter) => {
+                assert_eq!(fields.len(), 2);
+                assert_eq!(filter.len(), 3);
+                assert_eq!(filter[0].0.0, "archived_at");
+                assert_eq!(filter[0].1, Operator::Exists);
+                assert_eq!(filter[2].0.0, "created");
+            },
+            _ => panic!("Expected create partial index")
+        }
+
+        assert!(Parser::parse(b"create\0index\0where\0archived_at\0exists\0!false\0").is_err());
+        assert!(Parser::parse(b"create\0index\0price\0where\0").is_err());
+        assert!(Parser::parse(b"drop\0index\0price\0where\0price\0>\0&5\0").is_err());
+        assert!(Parser::parse(b"drop\0index\0").is_err());
+        assert!(Parser::parse(b"match\0price\0=\0&5").is_err());
+        assert!(Parser::parse(b"drop\0index\0price\0#name\0").is_err());
+        assert!(Parser::parse(b"drop\0table\0price\0").is_err());
+
+        match &Parser::parse(b"create\0expiry\0index\0created\0after\0&3600\0").unwrap()[0] {
+            QueryType::CreateExpiry(field, seconds) => assert_eq!((field.as_str(), *seconds), ("created", 3600)),
+            _ => panic!("Expected create expiry index")
+        }
+
+        assert!(matches!(Parser::parse(b"create\0expiry\0index\0expires_at\0").unwrap()[0], QueryType::CreateExpiry(_, 0)));
+        assert!(Parser::parse(b"create\0expiry\0index\0created\0after\0&-5\0").is_err());
+        assert!(Parser::parse(b"create\0expiry\0index\0created\0after\0#soon\0").is_err());
+        assert!(Parser::parse(b"create\0expiry\0index\0created\0updated\0").is_err());
+
+        assert!(matches!(Parser::parse(b"verify\0indexes\0").unwrap()[0], QueryType::VerifyIndexes(false)));
+        assert!(matches!(Parser::parse(b"verify\0indexes\0repair\0").unwrap()[0], QueryType::VerifyIndexes(true)));
+        assert!(Parser::parse(b"verify\0indexes\0price\0").is_err());
+        assert!(Parser::parse(b"verify\0index\0").is_err());
+    }
+
+    #[test]
+    fn parse_and() {
+        let query = b"match\0tenant\0=\0#acme\0and\0created\0>\0&5\0return\0";
+
+        match &Parser::parse(query).unwrap()[1] {
+            QueryType::Match(conditions) => {
+                assert_eq!(conditions.len(), 2);
+                assert_eq!(conditions[0].0.0, "tenant");
+                assert_eq!(conditions[1].0.0, "created");
+                assert_eq!(conditions[1].1, Operator::Greater);
+            },
+            _ => panic!("Expected match")
+        }
+
+        assert!(Parser::parse(b"match\0tenant\0=\0#acme\0and\0return\0").is_err());
+
+        match &Parser::parse(b"match\0about\0search\0#red shoes\0return\0").unwrap()[1] {
+            QueryType::Match(conditions) => assert_eq!(conditions[0].1, Operator::Search),
+            _ => panic!("Expected match")
+        }
+    }
+
+    #[test]
+    fn parse_collection() {
+        assert!(matches!(&Parser::parse(b"use\0users\0").unwrap()[0], QueryType::Use(name) if name == "users"));
+        assert!(matches!(&Parser::parse(b"create\0collection\0users\0").unwrap()[0], QueryType::CreateCollection(name) if name == "users"));
+        assert!(matches!(&Parser::parse(b"drop\0collection\0users\0").unwrap()[0], QueryType::DropCollection(name) if name == "users"));
+        assert!(matches!(Parser::parse(b"list\0collections\0").unwrap()[0], QueryType::ListCollections));
+
+        // a field named collection is still a record
+        assert!(matches!(Parser::parse(b"create\0collection\0#users\0").unwrap()[0], QueryType::Create(_)));
+
+        let query_types = Parser::parse(b"match\0users.name\0=\0#bob\0and\0age\0>\0&5\0set\0users.age\0&6\0").unwrap();
+
+        match (&query_types[0], &query_types[1], &query_types[2]) {
+            (QueryType::Set(pairs), QueryType::Match(conditions), QueryType::Collection(name)) => {
+                assert_eq!(pairs[0].0, "age");
+                assert_eq!(conditions[0].0.0, "name");
+                assert_eq!(name, "users");
+            },
+            _ => panic!("Expected match on a collection")
+        }
+
+        assert_eq!(Parser::parse(b"create\0index\0users.email\0").unwrap().len(), 2);
+        assert_eq!(Parser::parse(b"match\0name\0=\0#bob\0return\0").unwrap().len(), 2);
+        assert!(Parser::parse(b"match\0users.name\0=\0#bob\0return\0orders.name\0").is_err());
+        assert!(Parser::parse(b"create\0users.\0#bob\0").is_err());
+        assert!(Parser::parse(b"create\0a.b.c\0#bob\0").is_err());
+        assert!(Parser::parse(b"use\0users.name\0").is_err());
+        assert!(Parser::parse(b"use\0users\0orders\0").is_err());
+        assert!(Parser::parse(b"drop\0collection\0").is_err());
+    }
+
+    #[test]
+    fn parse_schema() {
+        match &Parser::parse(b"schema\0users.name\0text\0required\0min\0&1\0max\0&40\0").unwrap()[..] {
+            [QueryType::Schema(field, rule), QueryType::Collection(name)] => {
+                assert_eq!((field.as_str(), name.as_str()), ("name", "users"));
+                assert_eq!(rule.allowed, Allowed::Text);
+                assert!(rule.required);
+                assert_eq!(rule.o_max, Some(Scalar::Integer(scalar::Integer::new(40))));
+            },
+            _ => panic!("Expected schema on a collection")
+        }
+
+        match &Parser::parse(b"schema\0age\0number\0max\0%150.5\0").unwrap()[0] {
+            QueryType::Schema(_, rule) => assert!(!rule.required && rule.o_min.is_none() && rule.o_max.is_some()),
+            _ => panic!("Expected schema")
+        }
+
+        assert!(matches!(Parser::parse(b"schema\0strict\0").unwrap()[0], QueryType::SchemaStrict(true)));
+        assert!(matches!(Parser::parse(b"schema\0loose\0").unwrap()[0], QueryType::SchemaStrict(false)));
+        assert!(matches!(Parser::parse(b"schema\0strict\0boolean\0").unwrap()[0], QueryType::Schema(_, _)));
+        assert!(matches!(Parser::parse(b"list\0schema\0").unwrap()[0], QueryType::ListSchema));
+        assert!(matches!(Parser::parse(b"drop\0schema\0").unwrap()[0], QueryType::DropSchema));
+        assert!(matches!(Parser::parse(b"drop\0unused\0fields\0").unwrap()[0], QueryType::DropUnusedFields));
+
+        match &Parser::parse(b"rename\0field\0users.nmae\0users.name\0").unwrap()[..] {
+            [QueryType::RenameField(old, new), QueryType::Collection(collection)] => {
+                assert_eq!((old.as_str(), new.as_str(), collection.as_str()), ("nmae", "name", "users"));
+            },
+            _ => panic!("Expected rename field")
+        }
+
+        assert!(matches!(Parser::parse(b"rename\0field\0nmae\0"), Err(Fumble::Rename)));
+        assert!(matches!(Parser::parse(b"rename\0nmae\0name\0"), Err(Fumble::Rename)));
+        assert!(matches!(Parser::parse(b"rename\0field\0a\0b\0c\0"), Err(Fumble::Rename)));
+        assert!(matches!(Parser::parse(b"rename\0field\0users.a\0orders.b\0"), Err(Fumble::Collection)));
+        assert!(Parser::parse(b"drop\0unused\0").is_err());
+        assert!(Parser::parse(b"schema\0age\0").is_err());
+        assert!(Parser::parse(b"schema\0age\0float\0").is_err());
+        assert!(Parser::parse(b"schema\0age\0integer\0max\0").is_err());
+        assert!(Parser::parse(b"schema\0age\0integer\0max\0&5\0min\0&1\0").is_err());
+    }
+}
@@ -11,7 +11,7 @@ impl Serde {
 
             bytes.extend(field.as_bytes());
             bytes.push(0);
-            let scalar_type = scalar.as_bytes()[0];
+            let scalar_type = scalar.type_code();
             bytes.push(scalar_type);
             bytes.extend(scalar.to_string().as_bytes());
             bytes.push(0);
@@ -1,21 +1,26 @@
-use decimal::{ D8, D16, D32, D64, DecimalTrait };
+use decimal::{ D8, D16, D32, D64, DBig, DecimalTrait, Spec };
 use crate::def::Error;
+use crate::numeric::Numeric;
+use crate::integer::Integer;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Decimal {
     Tiny(D8),
     Small(D16),
     Medium(D32),
-    Large(D64)
+    Large(D64),
+    Huge(DBig)
 }
 
 impl Decimal {
-    pub fn unwrap(&self) -> isize {
+    // Huge does not fit in isize
+    pub fn unwrap(&self) -> Option<isize> {
         match self {
-            Self::Tiny(v) => v.unwrap() as isize,
-            Self::Small(v) => v.unwrap() as isize,
-            Self::Medium(v) => v.unwrap() as isize,
-            Self::Large(v) => v.unwrap() as isize
+            Self::Tiny(v) => Some(v.unwrap() as isize),
+            Self::Small(v) => Some(v.unwrap() as isize),
+            Self::Medium(v) => Some(v.unwrap() as isize),
+            Self::Large(v) => Some(v.unwrap() as isize),
+            Self::Huge(_) => None
         }
     }
 
@@ -25,6 +30,7 @@ impl Decimal {
             Self::Small(v) => v.as_bytes().to_vec(),
             Self::Medium(v) => v.as_bytes().to_vec(),
             Self::Large(v) => v.as_bytes().to_vec(),
+            Self::Huge(v) => v.as_bytes()
         }
     }
 
@@ -44,15 +50,17 @@ impl Decimal {
 
     pub fn from_string(s: String) -> Result<Self, Error> {
         if let Ok(tiny) = D8::from_string(s.clone()) {
-            return Result::Ok(Decimal::Tiny(tiny));
+            Ok(Decimal::Tiny(tiny))
         }else if let Ok(small) = D16::from_string(s.clone()) {
-            return Result::Ok(Decimal::Small(small));
+            Ok(Decimal::Small(small))
         }else if let Ok(medium) = D32::from_string(s.clone()) {
-            return Result::Ok(Decimal::Medium(medium));
+            Ok(Decimal::Medium(medium))
         }else if let Ok(large) = D64::from_string(s.clone()) {
-            return Result::Ok(Decimal::Large(large));
+            Ok(Decimal::Large(large))
+        }else if let Ok(huge) = DBig::from_string(s) {
+            Ok(Decimal::Huge(huge))
         }else {
-            return Result::Err(Error::CannotParse)
+            Err(Error::CannotParse)
         }
     }
 
@@ -62,15 +70,53 @@ impl Decimal {
             Self::Small(v) => v.to_string(),
             Self::Medium(v) => v.to_string(),
             Self::Large(v) => v.to_string(),
+            Self::Huge(v) => v.to_string()
         }
     }
 
-    pub fn to_integer(&self) -> isize {
-        match self {
-            Self::Tiny(v) => v.to_integer() as isize,
-            Self::Small(v) => v.to_integer() as isize,
-            Self::Medium(v) => v.to_integer() as isize,
-            Self::Large(v) => v.to_integer() as isize,
+    pub fn to_numeric(&self) -> Numeric {
+        let normalized = match self {
+            Self::Tiny(v) => v.to_isize(),
+            Self::Small(v) => v.to_isize(),
+            Self::Medium(v) => v.to_isize(),
+            Self::Large(v) => v.to_isize(),
+            Self::Huge(v) => {
+                return Numeric::from_big(v);
+            }
+        };
+
+        Numeric::from_decimal(normalized)
+    }
+
+    pub fn from_huge_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        match DBig::from_bytes(bytes) {
+            Ok(huge) => Ok(Self::Huge(huge)),
+            Err(_) => Err(Error::CannotParse)
+        }
+    }
+
+    pub fn from_integer(integer: &Integer) -> Self {
+        let s = integer.to_string() + ".0";
+
+        // every integer fits at least in huge
+        Self::from_string(s).unwrap()
+    }
+
+    pub fn is_huge(&self) -> bool {
+        matches!(self, Self::Huge(_))
+    }
+
+    // Rounds to the declared scale and then picks the smallest
+    // shape which can hold the rounded value.
+    pub fn conform(&self, spec: &Spec) -> Result<Self, Error> {
+        let big = match self {
+            Self::Huge(v) => v.clone(),
+            _ => DBig::from_string(self.to_string()).unwrap()
+        };
+
+        match spec.apply(&big) {
+            Ok(rounded) => Self::from_string(rounded.to_string()),
+            Err(_) => Err(Error::OutOfPrecision)
         }
     }
 }
\ No newline at end of file
@@ -2,5 +2,6 @@
 pub enum Error {
     CannotParse,
     IncorrectByteLength,
-    WrongCode
+    WrongCode,
+    OutOfPrecision
 }
\ No newline at end of file
@@ -1,4 +1,5 @@
 use crate::def::Error;
+use crate::numeric::Numeric;
 use decimal::MAX_SCALE;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -90,4 +91,8 @@ impl Integer {
         let multiplier = base.pow(MAX_SCALE as u32);
         self.unwrap() * (multiplier as isize)
     }
+
+    pub fn to_numeric(&self) -> Numeric {
+        Numeric::from_integer(self.unwrap())
+    }
 }
\ No newline at end of file
@@ -1,14 +1,25 @@
 mod def;
 mod decimal;
 mod integer;
+mod numeric;
 mod type_code;
 use core::panic;
+use std::cmp::Ordering;
 pub use decimal::Decimal;
 use fumble::Fumble;
+use def::Error;
 use type_code::{ Type, TypeCode };
 pub use integer::Integer;
+pub use numeric::Numeric;
+pub use ::decimal::{ Spec, Rounding };
 
-#[derive(Debug, Clone, PartialEq)]
+const FALSE_BYTE: u8 = 0;
+const TRUE_BYTE: u8 = 1;
+
+// Note: PartialEq and PartialOrd are not derived. Integer and
+// Decimal are compared on their numeric value, so &5 == %5.0.
+// Any other mix of types is neither equal nor ordered.
+#[derive(Debug, Clone)]
 pub enum Scalar {
     Boolean(bool),
     Text(String),
@@ -31,7 +42,8 @@ impl Scalar {
         }
     }
 
-    pub fn unwrap_decimal(&self) -> isize {
+    // None for a huge decimal
+    pub fn unwrap_decimal(&self) -> Option<isize> {
         match self {
             Self::Decimal(v) => v.unwrap(),
             _ => panic!("{:?}", "Incorrect unwrap")
@@ -45,10 +57,45 @@ impl Scalar {
         }
     }
 
+    pub fn to_numeric(&self) -> Option<Numeric> {
+        match self {
+            Self::Integer(v) => Some(v.to_numeric()),
+            Self::Decimal(v) => Some(v.to_numeric()),
+            _ => None
+        }
+    }
+
+    // Type code as told to the outside
+    pub fn type_code(&self) -> u8 {
+        match self {
+            Self::Boolean(_) => TypeCode::get_code(Type::Boolean),
+            Self::Text(_) => TypeCode::get_code(Type::Text),
+            Self::Decimal(_) => TypeCode::get_code(Type::Decimal),
+            Self::Integer(_) => TypeCode::get_code(Type::Integer)
+        }
+    }
+
+    // Shapes a numeric value as per the declared decimal spec.
+    // Integer turns into decimal. Other types are kept as it is.
+    pub fn conform(&self, spec: &Spec) -> Result<Self, Fumble> {
+        let decimal = match self {
+            Self::Decimal(v) => v.clone(),
+            Self::Integer(v) => Decimal::from_integer(v),
+            _ => {
+                return Ok(self.clone());
+            }
+        };
+
+        match decimal.conform(spec) {
+            Ok(conformed) => Ok(Self::Decimal(conformed)),
+            Err(_) => Err(Fumble::ScalarPrecision)
+        }
+    }
+
     pub fn as_bytes(&self) -> Vec<u8> {
         match self {
             Self::Integer(integer) => {
-                let tp = TypeCode::get_code(Type::INTEGER);
+                let tp = TypeCode::get_code(Type::Integer);
                 let mut result: Vec<u8> = vec![tp];
                 
                 result.extend(integer.as_bytes());
@@ -56,7 +103,12 @@ impl Scalar {
                 return result
             },
             Self::Decimal(v) => {
-                let tp = TypeCode::get_code(Type::DECIMAL);
+                let mut tp = TypeCode::get_code(Type::Decimal);
+
+                if v.is_huge() {
+                    tp = TypeCode::get_code(Type::Huge);
+                }
+
                 let mut result: Vec<u8> = vec![tp];
                 result.extend(v.as_bytes());
 
@@ -64,18 +116,18 @@ impl Scalar {
                 
             },
             Self::Boolean(v) => {
-                let tp = TypeCode::get_code(Type::BOOLEAN);
+                let tp = TypeCode::get_code(Type::Boolean);
                 let mut result: Vec<u8> = vec![tp];
                 if v == &true {
-                    result.push(1);
+                    result.push(TRUE_BYTE);
                 }else {
-                    result.push(1);
+                    result.push(FALSE_BYTE);
                 }
                 
                 return result
             },
             Self::Text(v) => {
-                let tp = TypeCode::get_code(Type::TEXT);
+                let tp = TypeCode::get_code(Type::Text);
                 let mut result: Vec<u8> = vec![tp];
                 result.extend(v.as_bytes().to_vec());
 
@@ -84,26 +136,61 @@ impl Scalar {
         }
     }
 
-    pub fn from_bytes(bytes: &[u8]) -> Self {
+    // Every byte slice is either decoded or refused with an error.
+    // It never panics, as bytes may come from a corrupted disk.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.is_empty() {
+            return Err(Error::IncorrectByteLength);
+        }
+
         // read first byte for type
         let code = bytes[0];
-        let tp = TypeCode::get_type(code).ok().unwrap();
+        let tp_result = TypeCode::get_type(code);
+        let payload = &bytes[1..];
 
-        match tp {
-            Type::BOOLEAN => {
-                if bytes[1] == 1 {
-                    return Self::Boolean(true)
+        match tp_result {
+            Err(err) => Err(err),
+            Ok(Type::Boolean) => {
+                if payload == [TRUE_BYTE] {
+                    Ok(Self::Boolean(true))
+                }else if payload == [FALSE_BYTE] {
+                    Ok(Self::Boolean(false))
                 }else {
-                    return Self::Boolean(false)
+                    Err(Error::CannotParse)
                 }
             },
-            Type::TEXT => Self::Text(String::from_utf8(bytes[1..bytes.len()].to_vec()).unwrap()),
-            Type::DECIMAL => Self::Decimal(Decimal::from_bytes(&bytes[1..bytes.len()]).unwrap()),
-            Type::INTEGER => Self::Integer(Integer::from_bytes(&bytes[1..bytes.len()]).unwrap())
+            Ok(Type::Text) => {
+                match String::from_utf8(payload.to_vec()) {
+                    Ok(text) => Ok(Self::Text(text)),
+                    Err(_) => Err(Error::CannotParse)
+                }
+            },
+            Ok(Type::Decimal) => {
+                match Decimal::from_bytes(payload) {
+                    Ok(decimal) => Ok(Self::Decimal(decimal)),
+                    Err(err) => Err(err)
+                }
+            },
+            Ok(Type::Huge) => {
+                match Decimal::from_huge_bytes(payload) {
+                    Ok(decimal) => Ok(Self::Decimal(decimal)),
+                    Err(err) => Err(err)
+                }
+            },
+            Ok(Type::Integer) => {
+                match Integer::from_bytes(payload) {
+                    Ok(integer) => Ok(Self::Integer(integer)),
+                    Err(err) => Err(err)
+                }
+            }
         }
     }
 
     pub fn from_type_string(bytes: &[u8]) -> Result<Self, Fumble> {
+        if bytes.is_empty() {
+            return Err(Fumble::ScalarType);
+        }
+
         let code = bytes[0];
         let tp_result = TypeCode::get_type(code);
         
@@ -111,23 +198,18 @@ impl Scalar {
             Err(_err) => Err(Fumble::ScalarType),
             Ok(tp) => {
                 match tp {
-                    Type::BOOLEAN => {
-                        if bytes[1] as char == 't' 
-                        && bytes[2] as char == 'r' 
-                        && bytes[3] as char == 'u' 
-                        && bytes[4] as char == 'e' {
-                            return Ok(Self::Boolean(true))
-                        }else if bytes[1] as char == 'f' 
-                        && bytes[1] as char == 'a' 
-                        && bytes[2] as char == 'l' 
-                        && bytes[3] as char == 's' 
-                        && bytes[4] as char == 'e' {
-                            return Ok(Self::Boolean(false))
+                    Type::Boolean => {
+                        let payload = &bytes[1..];
+
+                        if payload == b"true" {
+                            Ok(Self::Boolean(true))
+                        }else if payload == b"false" {
+                            Ok(Self::Boolean(false))
                         }else {
-                            return Err(Fumble::ScalarBoolean)
+                            Err(Fumble::ScalarBoolean)
                         }
                     },
-                    Type::TEXT => {
+                    Type::Text => {
                         let parse_result = String::from_utf8(bytes[1..bytes.len()].to_vec());
 
                         match parse_result {
@@ -135,7 +217,7 @@ impl Scalar {
                             Ok(parsed) => Ok(Self::Text(parsed))
                         }
                     },
-                    Type::DECIMAL => {
+                    Type::Decimal | Type::Huge => {
                         let parse_string = String::from_utf8(bytes[1..bytes.len()].to_vec());
 
                         match parse_string {
@@ -150,7 +232,7 @@ impl Scalar {
                             }
                         }
                     },
-                    Type::INTEGER => {
+                    Type::Integer => {
                         let parse_string = String::from_utf8(bytes[1..bytes.len()].to_vec());
 
                         match parse_string {
@@ -203,3 +285,133 @@ impl Scalar {
         }
     }
 }
+
+impl PartialEq for Scalar {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Scalar {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Self::Boolean(left), Self::Boolean(right)) => left.partial_cmp(right),
+            (Self::Text(left), Self::Text(right)) => left.partial_cmp(right),
+            _ => {
+                match (self.to_numeric(), other.to_numeric()) {
+                    (Some(left), Some(right)) => left.partial_cmp(&right),
+                    _ => None
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+    use rand::rngs::ThreadRng;
+
+    const ROUNDS: usize = 2000;
+
+    fn random_text(rng: &mut ThreadRng) -> String {
+        let len = rng.gen_range(0..50);
+
+        (0..len).map(|_| rng.gen::<char>()).collect()
+    }
+
+    fn random_huge(rng: &mut ThreadRng) -> String {
+        let left: String = (0..rng.gen_range(1..40)).map(|_| rng.gen_range(b'0'..=b'9') as char).collect();
+        let right: String = (0..rng.gen_range(1..40)).map(|_| rng.gen_range(b'0'..=b'9') as char).collect();
+        let sign = if rng.gen::<bool>() { "-" } else { "" };
+
+        format!("{}{}.{}", sign, left, right)
+    }
+
+    // One random scalar of every variant and width
+    fn random_scalars(rng: &mut ThreadRng) -> Vec<Scalar> {
+        vec![
+            Scalar::Boolean(true),
+            Scalar::Boolean(false),
+            Scalar::Text(random_text(rng)),
+            Scalar::Integer(Integer::Tiny(rng.gen())),
+            Scalar::Integer(Integer::Small(rng.gen())),
+            Scalar::Integer(Integer::Medium(rng.gen())),
+            Scalar::Integer(Integer::Large(rng.gen())),
+            Scalar::Decimal(Decimal::from_bytes(&rng.gen::<[u8; 1]>()).unwrap()),
+            Scalar::Decimal(Decimal::from_bytes(&rng.gen::<[u8; 2]>()).unwrap()),
+            Scalar::Decimal(Decimal::from_bytes(&rng.gen::<[u8; 4]>()).unwrap()),
+            Scalar::Decimal(Decimal::from_bytes(&rng.gen::<[u8; 8]>()).unwrap()),
+            Scalar::Decimal(Decimal::from_string(random_huge(rng)).unwrap())
+        ]
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..ROUNDS {
+            for scalar in random_scalars(&mut rng) {
+                let bytes = scalar.as_bytes();
+                let decoded = Scalar::from_bytes(&bytes).unwrap();
+
+                // same bytes means same variant and same width
+                assert_eq!(decoded.as_bytes(), bytes);
+                assert_eq!(decoded.to_string(), scalar.to_string());
+            }
+        }
+    }
+
+    #[test]
+    fn unwrap_decimal() {
+        let small = Scalar::Decimal(Decimal::from_string("1.5".to_owned()).unwrap());
+        let huge = Scalar::Decimal(Decimal::from_string("123456789012345678901234567890.5".to_owned()).unwrap());
+
+        assert!(small.unwrap_decimal().is_some());
+        assert_eq!(huge.unwrap_decimal(), None);
+    }
+
+    #[test]
+    fn boolean() {
+        assert!(!Scalar::from_bytes(&Scalar::Boolean(false).as_bytes()).unwrap().unwrap_boolean());
+        assert!(!Scalar::from_type_string(b"!false").unwrap().unwrap_boolean());
+        assert!(Scalar::from_type_string(b"!true").unwrap().unwrap_boolean());
+        assert!(Scalar::from_type_string(b"!tru").is_err());
+        assert!(Scalar::from_type_string(b"!").is_err());
+        assert!(Scalar::from_type_string(b"").is_err());
+        assert!(Scalar::from_bytes(&[TypeCode::get_code(Type::Boolean), 2]).is_err());
+    }
+
+    #[test]
+    fn corrupted() {
+        let mut rng = rand::thread_rng();
+
+        assert!(Scalar::from_bytes(&[]).is_err());
+        assert!(Scalar::from_bytes(&[0, 1]).is_err());
+        assert!(Scalar::from_bytes(&[TypeCode::get_code(Type::Integer), 1, 2, 3]).is_err());
+        assert!(Scalar::from_bytes(&[TypeCode::get_code(Type::Text), 0xFF]).is_err());
+
+        // Any random bytes either decode or fail but never panic
+        for _ in 0..(ROUNDS * 10) {
+            let len = rng.gen_range(0..20);
+            let bytes: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+
+            let _ = Scalar::from_bytes(&bytes);
+            let _ = Scalar::from_type_string(&bytes);
+        }
+
+        // Flip a byte of valid scalars
+        for _ in 0..ROUNDS {
+            for scalar in random_scalars(&mut rng) {
+                let mut bytes = scalar.as_bytes();
+                let index = rng.gen_range(0..bytes.len());
+                bytes[index] = rng.gen();
+
+                if let Ok(decoded) = Scalar::from_bytes(&bytes) {
+                    assert_eq!(decoded.as_bytes(), bytes);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,103 @@
+/// Numeric is the common ordered form of Integer and Decimal.
+/// Both are scaled up to MAX_SCALE so that &5 and %5.0 end up
+/// on the same value, whichever width variant holds them.
+/// i128 is used as i64::MAX scaled by 10^MAX_SCALE can't fit in isize.
+/// Only a huge decimal which can't be held that way stays as DBig.
+use std::cmp::Ordering;
+use decimal::{ DBig, MAX_SCALE };
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Numeric {
+    Fixed(i128),
+    Huge(DBig)
+}
+
+impl Numeric {
+    pub fn from_integer(value: isize) -> Self {
+        let base: i128 = 10;
+        let multiplier = base.pow(MAX_SCALE as u32);
+
+        Self::Fixed((value as i128) * multiplier)
+    }
+
+    // value should already be normalized to MAX_SCALE
+    pub fn from_decimal(value: isize) -> Self {
+        Self::Fixed(value as i128)
+    }
+
+    pub fn from_big(value: &DBig) -> Self {
+        match value.to_scaled(MAX_SCALE) {
+            Some(scaled) => Self::Fixed(scaled),
+            None => Self::Huge(value.clone())
+        }
+    }
+
+    fn to_big(&self) -> DBig {
+        match self {
+            Self::Fixed(value) => DBig::from_scaled(*value, MAX_SCALE),
+            Self::Huge(value) => value.clone()
+        }
+    }
+}
+
+impl PartialOrd for Numeric {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Numeric {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Fixed(left), Self::Fixed(right)) => left.cmp(right),
+            _ => self.to_big().cmp(&other.to_big())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ Decimal, Integer };
+
+    #[test]
+    fn integer_equals_decimal() {
+        let integer = Integer::new(5).to_numeric();
+        let decimal = Decimal::from_string("5.0".to_owned()).unwrap().to_numeric();
+
+        assert_eq!(integer, decimal)
+    }
+
+    #[test]
+    fn widths_collide() {
+        let tiny = Integer::Tiny(5).to_numeric();
+        let large = Integer::Large(5).to_numeric();
+
+        assert_eq!(tiny, large)
+    }
+
+    #[test]
+    fn order() {
+        let ten = Integer::new(10).to_numeric();
+        let ten_and_half = Decimal::from_string("10.5".to_owned()).unwrap().to_numeric();
+        let minus_half = Decimal::from_string("-0.5".to_owned()).unwrap().to_numeric();
+
+        assert!(ten_and_half > ten);
+        assert!(minus_half < ten);
+        assert!(Integer::new(i64::MAX as isize).to_numeric() > ten_and_half);
+    }
+
+    #[test]
+    fn huge() {
+        let huge = Decimal::from_string("123456789012345678901234567890.5".to_owned()).unwrap().to_numeric();
+        let fine = Decimal::from_string("0.000000000001".to_owned()).unwrap().to_numeric();
+        let max = Integer::new(i64::MAX as isize).to_numeric();
+
+        assert!(matches!(huge, Numeric::Huge(_)));
+        assert!(matches!(fine, Numeric::Huge(_)));
+        assert!(huge > max);
+        assert!(fine > Integer::new(0).to_numeric());
+        assert!(fine < Decimal::from_string("0.1".to_owned()).unwrap().to_numeric());
+        assert_eq!(Decimal::from_string("123456789.5".to_owned()).unwrap().to_numeric(), Numeric::Fixed(1234567895000000000));
+    }
+}
@@ -3,14 +3,18 @@ use crate::def::Error;
 const BOOLEAN: u8 = 33; // !
 const STRING: u8 = 35; // #
 const DECIMAL: u8 = 37; // %
+// Huge decimal only tells the stored bytes apart.
+// To the outside it is still a decimal.
+const HUGE_DECIMAL: u8 = 36; // $
 const INTEGER: u8 = 38; // &
 
 #[derive(Debug, PartialEq)]
 pub enum Type {
-    TEXT,
-    BOOLEAN,
-    DECIMAL,
-    INTEGER
+    Text,
+    Boolean,
+    Decimal,
+    Huge,
+    Integer
 }
 
 pub struct TypeCode;
@@ -18,22 +22,25 @@ pub struct TypeCode;
 impl TypeCode {
     pub fn get_code(t: Type) -> u8 {
         match t {
-            Type::TEXT => STRING,
-            Type::BOOLEAN => BOOLEAN,
-            Type::DECIMAL => DECIMAL,
-            Type::INTEGER => INTEGER
+            Type::Text => STRING,
+            Type::Boolean => BOOLEAN,
+            Type::Decimal => DECIMAL,
+            Type::Huge => HUGE_DECIMAL,
+            Type::Integer => INTEGER
         }
     }
 
     pub fn get_type(code: u8) -> Result<Type, Error> {
         if code == STRING {
-            Ok(Type::TEXT)
+            Ok(Type::Text)
         }else if code == BOOLEAN {
-            Ok(Type::BOOLEAN)
+            Ok(Type::Boolean)
         }else if code == DECIMAL {
-            Ok(Type::DECIMAL)
+            Ok(Type::Decimal)
+        }else if code == HUGE_DECIMAL {
+            Ok(Type::Huge)
         }else if code == INTEGER {
-            Ok(Type::INTEGER)
+            Ok(Type::Integer)
         }else {
             Err(Error::WrongCode)
         }
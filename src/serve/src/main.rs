@@ -1,9 +1,39 @@
 use std::{
     io::prelude::*,
-    net::TcpListener
+    io::ErrorKind,
+    net::{ TcpListener, TcpStream },
+    thread,
+    time::Duration
 };
 
 use marketplace::Marketplace;
+use fumble::Fumble;
+
+// Room for a document at its size limit, written out as a query
+const QUERY_MAX_SIZE: u64 = 32 * 1024 * 1024;
+// How long a client may keep the others waiting while its query comes in
+const READ_TIMEOUT_SECS: u64 = 5;
+
+// A query comes after its size in four bytes, big endian, so it is
+// known where it ends however many reads it takes. One above the
+// limit is turned down before any of it is read.
+fn read_query(stream: &mut TcpStream) -> std::io::Result<Option<Vec<u8>>> {
+    let mut size: [u8; 4] = [0; 4];
+
+    stream.read_exact(&mut size)?;
+
+    let size = u32::from_be_bytes(size) as u64;
+
+    if size > QUERY_MAX_SIZE {
+        return Ok(None);
+    }
+
+    let mut bytes: Vec<u8> = vec![0; size as usize];
+
+    stream.read_exact(&mut bytes)?;
+
+    Ok(Some(bytes))
+}
 
 fn main() -> std::io::Result<()> {
     let instant = std::time::Instant::now();
@@ -21,28 +51,41 @@ fn main() -> std::io::Result<()> {
     println!("{:?}", instant.elapsed());
     let listener = TcpListener::bind("127.0.0.1:41221").unwrap();
 
-    for stream_result in listener.incoming() {
-        match stream_result {
-            Ok(mut stream) => {
-                let mut read: [u8; 10000] = [0; 10000];
-
-                match stream.read(&mut read) {
-                    Ok(n) => {
-                        if n == 0 {
-                            break;
-                        }
-
-                        let result = marketplace.query(&read[0..n]);
-                        stream.write(result.as_bytes()).unwrap();
-                        
-                    },
-                    Err(err) => panic!("{:?}", err)
+    // Idle work is looked at before any client, as it is after each
+    listener.set_nonblocking(true).unwrap();
+
+    loop {
+        match listener.accept() {
+            Ok((mut stream, _)) => {
+                listener.set_nonblocking(true).unwrap();
+                stream.set_nonblocking(false).unwrap();
+                stream.set_read_timeout(Some(Duration::from_secs(READ_TIMEOUT_SECS))).unwrap();
+
+                let result = match read_query(&mut stream) {
+                    Ok(Some(bytes)) => marketplace.query(&bytes),
+                    Ok(None) => Fumble::ScalarsCombinedSize.unwrap(),
+                    // A client too slow, gone or sending less than it said
+                    // only loses its own connection
+                    Err(err) => {
+                        println!("{:?}", err);
+                        continue;
+                    }
+                };
+
+                // Client may be gone by now, which is no reason to stop
+                if let Err(err) = stream.write_all(result.as_bytes()) {
+                    println!("{:?}", err);
+                }
+            },
+            // No client waiting, so writes are synced and files compacted
+            // on time. With nothing left to do it blocks for the next one.
+            Err(err) if err.kind() == ErrorKind::WouldBlock => {
+                match marketplace.idle() {
+                    Some(wait) => thread::sleep(wait),
+                    None => listener.set_nonblocking(false).unwrap()
                 }
-                
             },
             Err(err) => panic!("{:?}", err)
         }
     }
-
-    Ok(())
 }
@@ -1,2 +1,7 @@
 pub const FOLDER_NAME: &str = "db";
-pub const DATA_FILE_NAME: &str = "data";
\ No newline at end of file
+pub const DATA_FILE_NAME: &str = "data";
+pub const INDEX_FILE_NAME: &str = "index";
+pub const COLLECTION_FILE_NAME: &str = "collections";
+// Group durability syncs after this many writes or milliseconds
+pub const GROUP_WRITES: usize = 100;
+pub const GROUP_MILLIS: u64 = 10;
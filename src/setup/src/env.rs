@@ -1,6 +1,9 @@
 use crate::def;
 use std::env;
-use std::path::PathBuf;
+use std::fs::{ self, File };
+use std::io::Write;
+use std::io::ErrorKind;
+use std::path::{ Path, PathBuf };
 
 pub enum Mode {
     WithoutInventory,
@@ -8,6 +11,20 @@ pub enum Mode {
     OnlyInventory
 }
 
+// When a write is synced to disk. Only write keeps every change
+// through a power failure, the others may lose the latest ones. A
+// change of more than one write is synced before it goes out in any
+// case, so none of them leaves part of a change on disk.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Durability {
+    // left to the operating system
+    None,
+    // before the write is answered
+    Write,
+    // once this many writes or milliseconds have gone unsynced
+    Group(usize, u64)
+}
+
 pub fn set_folder_path(o_folder_path: Option<&str>) {
     let mut folder_path = PathBuf::from(def::DATA_FILE_NAME);
 
@@ -22,12 +39,175 @@ pub fn get_folder_path() -> String {
     env::var("FOLDER_PATH").unwrap_or_else(|_| def::FOLDER_NAME.to_string())
 }
 
-pub fn set_index_setup() {
+// Goods are compressed where it saves space, unless turned off
+pub fn set_compression(is_on: bool) {
+    env::set_var("COMPRESSION", if is_on { "on" } else { "off" });
+}
+
+pub fn get_compression() -> bool {
+    env::var("COMPRESSION").map_or(true, |value| value != "off")
+}
+
+// Written as none, write, group or group:<writes>:<millis>
+pub fn set_durability(durability: Durability) {
+    let value = match durability {
+        Durability::None => String::from("none"),
+        Durability::Write => String::from("write"),
+        Durability::Group(writes, millis) => format!("group:{}:{}", writes, millis)
+    };
+
+    env::set_var("DURABILITY", value);
+}
+
+// Every write is synced unless told otherwise
+pub fn get_durability() -> Durability {
+    let value = env::var("DURABILITY").unwrap_or_default();
+    let parts: Vec<&str> = value.split(':').collect();
+
+    match parts.as_slice() {
+        ["none"] => Durability::None,
+        ["group"] => Durability::Group(def::GROUP_WRITES, def::GROUP_MILLIS),
+        ["group", writes, millis] => {
+            match (writes.parse::<usize>(), millis.parse::<u64>()) {
+                (Ok(writes), Ok(millis)) if writes > 0 => Durability::Group(writes, millis),
+                _ => panic!("DURABILITY should be none, write, group or group:<writes>:<millis>")
+            }
+        },
+        [""] | ["write"] => Durability::Write,
+        _ => panic!("DURABILITY should be none, write, group or group:<writes>:<millis>")
+    }
+}
+
+// Index definitions, schema rules, formats and collection names are kept
+// as opaque records, each one written as [length u16][bytes].
+fn setup_path(file_name: &str) -> PathBuf {
+    let mut path = PathBuf::from(get_folder_path());
+    path.push(file_name);
+    path
+}
+
+fn write_records(path: &Path, records: &[Vec<u8>]) {
+    let mut bytes: Vec<u8> = Vec::new();
+
+    for record in records {
+        let len = record.len() as u16;
+
+        bytes.extend(len.to_le_bytes());
+        bytes.extend(record);
+    }
+
+    // Write aside and swap, so a crash never leaves half a setup.
+    // File is synced before the swap, so the swap never puts an empty
+    // file in place, and the folder after, so the swap itself lasts.
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+
+    let write_result = File::create(&temp_path).and_then(|mut file| {
+        file.write_all(&bytes)?;
+        file.sync_all()
+    });
+
+    if let Err(err) = write_result {
+        panic!("{:?}", err);
+    }
+
+    if let Err(err) = sync_folder(path) {
+        panic!("{:?}", err);
+    }
+
+    if let Err(err) = fs::rename(&temp_path, path) {
+        panic!("{:?}", err);
+    }
+
+    if let Err(err) = sync_folder(path) {
+        panic!("{:?}", err);
+    }
+}
+
+fn sync_folder(path: &Path) -> std::io::Result<()> {
+    match path.parent() {
+        Some(folder) if !folder.as_os_str().is_empty() => File::open(folder)?.sync_all(),
+        _ => File::open(".")?.sync_all()
+    }
+}
+
+fn read_records(path: &Path) -> Vec<Vec<u8>> {
+    let mut records: Vec<Vec<u8>> = Vec::new();
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            if err.kind() == ErrorKind::NotFound {
+                return records;
+            }
+
+            panic!("{:?}", err);
+        }
+    };
+    let mut pointer = 0;
+
+    while pointer + 2 <= bytes.len() {
+        let len = u16::from_le_bytes([bytes[pointer], bytes[pointer + 1]]) as usize;
+        let start = pointer + 2;
+        let end = start + len;
+
+        if end > bytes.len() {
+            panic!("Setup is corrupted");
+        }
+
+        records.push(bytes[start..end].to_vec());
+        pointer = end;
+    }
+
+    if pointer != bytes.len() {
+        panic!("Setup is corrupted");
+    }
+
+    records
+}
+
+// File name tells which collection the indexes belong to
+pub fn set_index_setup(file_name: &str, definitions: &[Vec<u8>]) {
+    write_records(&setup_path(file_name), definitions)
+}
 
+pub fn get_index_setup(file_name: &str) -> Vec<Vec<u8>> {
+    read_records(&setup_path(file_name))
 }
 
-pub fn has_index_setup() -> bool {
-    true
+pub fn has_index_setup(file_name: &str) -> bool {
+    setup_path(file_name).exists()
+}
+
+pub fn set_schema_setup(file_name: &str, rules: &[Vec<u8>]) {
+    write_records(&setup_path(file_name), rules)
+}
+
+pub fn get_schema_setup(file_name: &str) -> Vec<Vec<u8>> {
+    read_records(&setup_path(file_name))
+}
+
+// Tells how the files of a collection are written
+pub fn set_format_setup(file_name: &str, records: &[Vec<u8>]) {
+    write_records(&setup_path(file_name), records)
+}
+
+pub fn get_format_setup(file_name: &str) -> Vec<Vec<u8>> {
+    read_records(&setup_path(file_name))
+}
+
+pub fn set_collections(names: &[String]) {
+    let records: Vec<Vec<u8>> = names.iter().map(|name| name.as_bytes().to_vec()).collect();
+
+    write_records(&setup_path(def::COLLECTION_FILE_NAME), &records)
+}
+
+pub fn get_collections() -> Vec<String> {
+    read_records(&setup_path(def::COLLECTION_FILE_NAME)).into_iter().map(|record| {
+        match String::from_utf8(record) {
+            Ok(name) => name,
+            Err(_) => panic!("Setup is corrupted")
+        }
+    }).collect()
 }
 
 pub fn get_mode() -> Mode {
@@ -51,4 +231,40 @@ mod tests {
 
         assert_eq!(get_folder_path(), another_folder_path);
     }
+
+    #[test]
+    fn durability() {
+        for durability in [Durability::None, Durability::Write, Durability::Group(50, 5)] {
+            set_durability(durability);
+
+            assert_eq!(get_durability(), durability);
+        }
+
+        env::set_var("DURABILITY", "group");
+
+        assert_eq!(get_durability(), Durability::Group(def::GROUP_WRITES, def::GROUP_MILLIS));
+
+        env::remove_var("DURABILITY");
+
+        assert_eq!(get_durability(), Durability::Write);
+    }
+
+    #[test]
+    fn index_setup() {
+        let mut path = std::env::temp_dir();
+        path.push(format!("index_setup_{}", std::process::id()));
+
+        assert!(read_records(&path).is_empty());
+
+        let definitions = vec![vec![1], vec![], vec![3, 4, 5]];
+        write_records(&path, &definitions);
+
+        assert_eq!(read_records(&path), definitions);
+
+        write_records(&path, &[]);
+
+        assert!(read_records(&path).is_empty());
+
+        fs::remove_file(&path).unwrap();
+    }
 }
@@ -34,6 +34,39 @@ pub fn file(file_name: &str) -> String {
     }
 }
 
+pub fn has_file(file_name: &str) -> bool {
+    let mut full_path = PathBuf::from(env::get_folder_path());
+
+    full_path.push(file_name);
+    full_path.exists()
+}
+
+// Nothing to remove is fine, as a file is only made on first use
+pub fn remove_file(file_name: &str) {
+    let mut full_path = PathBuf::from(env::get_folder_path());
+
+    full_path.push(file_name);
+
+    if let Err(err) = std::fs::remove_file(full_path) {
+        if err.kind() != ErrorKind::NotFound {
+            panic!("{:?}", err);
+        }
+    }
+}
+
+// Takes the place of any file already at the new name
+pub fn rename_file(file_name: &str, new_file_name: &str) {
+    let mut full_path = PathBuf::from(env::get_folder_path());
+    let mut new_full_path = PathBuf::from(env::get_folder_path());
+
+    full_path.push(file_name);
+    new_full_path.push(new_file_name);
+
+    if let Err(err) = std::fs::rename(full_path, new_full_path) {
+        panic!("{:?}", err);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,9 +1,11 @@
 
 mod inventory;
 mod merchandise;
+use std::time::Duration;
 use estate::WholeNumber;
 use fumble::Fumble;
-use godown::{ Godown, Logistics, Address as GodownAddress, Item as LogisticsItem };
+use godown::{ Godown, Logistics, Good, Address as GodownAddress, Item as LogisticsItem };
+pub use godown::ledger_file_name;
 pub use godown::LogisticsMode as SessionMode;
 pub use inventory::Inventory;
 pub use merchandise::Merchandise;
@@ -26,13 +28,34 @@ pub enum Token<Size> {
     Both(GodownAddress, Size)
 }
 
+impl<Size: WholeNumber> Token<Size> {
+    pub fn get_godown_address(&self) -> Option<GodownAddress> {
+        match self {
+            Self::Godown(address) | Self::Both(address, _) => Some(*address),
+            Self::Inventory(_) => None
+        }
+    }
+
+    // Same token after compaction moved its good
+    pub fn relocate(&self, address: GodownAddress) -> Self {
+        match self {
+            Self::Godown(_) => Self::Godown(address),
+            Self::Both(_, size) => Self::Both(address, *size),
+            Self::Inventory(_) => panic!("Inventory has nothing to relocate")
+        }
+    }
+}
+
 
 pub struct Warehouse<Size, Item>
 where Size: WholeNumber, Item: Merchandise 
 {
     o_godown: Option<Godown>,
     o_inventory: Option<Inventory<Size, Item>>,
-    o_logistics: Option<Logistics>
+    o_logistics: Option<Logistics>,
+    // Corrupted goods the last session stepped over. Those found
+    // while initializing stay on disk with their estate occupied.
+    skipped: usize
 }
 
 impl<Size: WholeNumber, Item: Merchandise> Warehouse<Size, Item> {
@@ -59,7 +82,8 @@ impl<Size: WholeNumber, Item: Merchandise> Warehouse<Size, Item> {
         Self {
             o_godown,
             o_inventory,
-            o_logistics: None
+            o_logistics: None,
+            skipped: 0
         }
     }
 
@@ -137,58 +161,60 @@ impl<Size: WholeNumber, Item: Merchandise> Warehouse<Size, Item> {
         } 
     }
 
-    pub fn remove(&mut self, token: Token<Size>){
+    pub fn remove(&mut self, token: &Token<Size>) -> Result<(), Fumble> {
         match  token {
             Token::Both(godown_address, inventory_address) => {
                 if let Some(godown) = self.o_godown.as_mut() {
-                    godown.remove(godown_address)   
+                    godown.remove(*godown_address)?;
                 }
                 
                 if let Some(inventory) = self.o_inventory.as_mut() {
-                    inventory.remove(inventory_address);
+                    inventory.remove(*inventory_address);
                 }
             },
             Token::Godown(godown_address) => {
                 if let Some(godown) = self.o_godown.as_mut() {
-                    godown.remove(godown_address);     
+                    godown.remove(*godown_address)?;
                 }
             },
 
             Token::Inventory(inventory_address) => {
                 if let Some(inventory) = self.o_inventory.as_mut() {
-                    inventory.remove(inventory_address);
+                    inventory.remove(*inventory_address);
                 }
             }
-        } 
+        }
+
+        Ok(())
     }
 
-    pub fn get(&mut self, token: &Token<Size>) -> Option<Item>{
+    // Err if the token points where nothing is kept
+    pub fn get(&mut self, token: &Token<Size>) -> Result<Item, Fumble>{
         match  token {
-            Token::Both(_, inventory_address) => {
-                if let Some(inventory) = self.o_inventory.as_mut() {
-                    Some(inventory.get(*inventory_address).unwrap().clone())
-                }else {
-                    None
+            Token::Both(_, inventory_address) | Token::Inventory(inventory_address) => {
+                match self.o_inventory.as_mut().and_then(|inventory| inventory.get(*inventory_address)) {
+                    Some(item) => Ok(item.clone()),
+                    None => Err(Fumble::Missing)
                 }
             },
             Token::Godown(godown_address) => {
                 if let Some(godown) = self.o_godown.as_mut() {
-                    Some(Item::from_good(godown.get(*godown_address)))
-                }else {
-                    None
-                }
-            },
-
-            Token::Inventory(inventory_address) => {
-                if let Some(inventory) = self.o_inventory.as_mut() {
-                    Some(inventory.get(*inventory_address).unwrap().clone())
+                    match godown.get(*godown_address) {
+                        Ok(good) => Item::from_good(good),
+                        Err(err) => Err(err)
+                    }
                 }else {
-                    None
+                    Err(Fumble::Missing)
                 }
             }
         }
     }
 
+    // Where the good of token starts in the godown file
+    pub fn get_pointer(&self, token: &Token<Size>) -> Option<usize> {
+        self.o_godown.as_ref()?.get_pointer(token.get_godown_address()?)
+    }
+
     pub fn start_session(&mut self, mode: SessionMode) {
         if self.o_godown.is_none() {
             panic!("Session cannot run in this Warehouse mode");
@@ -196,72 +222,134 @@ impl<Size: WholeNumber, Item: Merchandise> Warehouse<Size, Item> {
             panic!("Session is already running")
         }else{
             self.o_logistics = Some(Logistics::new(mode));
+            self.skipped = 0;
         }
     }
 
+    pub fn get_skipped(&self) -> usize {
+        self.skipped
+    }
+
     pub fn session_items(&mut self) -> Vec<SessionItem<Token<Size>, Item>> {
         let mut session_items: Vec<SessionItem<Token<Size>, Item>> = Vec::new();
-        
-        if let Some(godown) = self.o_godown.as_mut() {
-            if let Some(mut logistics) = self.o_logistics.as_mut() {
-                godown.transfer_chunk(&mut logistics);
-                // println!("Recursion is happening: {:?}", logistics.get_has_done());
-                let logistics_items = logistics.unload();
-                
-                // It might be possible that no items received
-                // May be due to all deleted item in this session
-                // We may need to call again until has done is true
-                if logistics_items.len() == 0 && logistics.get_has_done() {
-                    return session_items;
-                }else if logistics_items.len() == 0 {
-                    return self.session_items();
-                }else {
-                    // deal with if both inventory and godown is present
-                    // deal if only godown is present
-                    match logistics.get_mode() {
-                        SessionMode::Uninitialize => {
-                            for logistics_item in logistics_items {
-                                match logistics_item {
-                                    LogisticsItem::WithoutAddress(good) => {
-                                        let item = Item::from_good(good);
-                                        session_items.push(SessionItem::WithoutToken(item));
-                                    },
-                                    _ => {}
+        let godown = match self.o_godown.as_mut() {
+            Some(godown) => godown,
+            None => panic!("Can't use session without godown")
+        };
+        let logistics = match self.o_logistics.as_mut() {
+            Some(logistics) => logistics,
+            None => panic!("Cannot find logistics")
+        };
+
+        // A chunk might give no items, as all of it was removed
+        // or corrupted goods. Empty items would be read as session
+        // end by caller, so chunks are read until some come.
+        while session_items.is_empty() && !logistics.get_has_done() {
+            godown.transfer_chunk(logistics);
+
+            let logistics_items = logistics.unload();
+
+            match logistics.get_mode() {
+                SessionMode::Uninitialize => {
+                    for logistics_item in logistics_items {
+                        match logistics_item {
+                            LogisticsItem::WithoutAddress(good) => {
+                                match Item::from_good(good) {
+                                    Ok(item) => session_items.push(SessionItem::WithoutToken(item)),
+                                    Err(_) => self.skipped += 1
+                                }
+                            },
+                            LogisticsItem::Corrupted(_) => self.skipped += 1,
+                            _ => {}
+                        }
+                    }
+                },
+                SessionMode::Initialize => {
+                    for logistics_item in logistics_items {
+                        match logistics_item {
+                            LogisticsItem::WithAddress(address, good) => {
+                                let item_result = Item::from_good(good);
+
+                                // It stays on disk as it is. Just not served.
+                                if item_result.is_err() {
+                                    self.skipped += 1;
+                                    continue;
                                 }
-                            }        
-                        },
-                        SessionMode::Initialize => {
-                            for logistics_item in logistics_items {
-                                match logistics_item {
-                                    LogisticsItem::WithAddress(address, good) => {
-                                        let item = Item::from_good(good);
-                                        
-                                        if let Some(inventory) = self.o_inventory.as_mut() {
-                                            let inventory_item = item.clone();
-                                            let inventory_address = inventory.place(inventory_item);
-
-                                            session_items.push(SessionItem::WithToken(Token::Both(address, inventory_address), item))
-                                        }else {
-                                            session_items.push(SessionItem::WithToken(Token::Godown(address), item))
-                                        }
-                                    },
-                                    _ => {}
+
+                                let item = item_result.ok().unwrap();
+
+                                if let Some(inventory) = self.o_inventory.as_mut() {
+                                    let inventory_item = item.clone();
+                                    let inventory_address = inventory.place(inventory_item);
+
+                                    session_items.push(SessionItem::WithToken(Token::Both(address, inventory_address), item))
+                                }else {
+                                    session_items.push(SessionItem::WithToken(Token::Godown(address), item))
                                 }
-                            }
+                            },
+                            LogisticsItem::Corrupted(_) => self.skipped += 1,
+                            _ => {}
                         }
                     }
                 }
-                
-            }else {
-                panic!("Cannot find logistics")
             }
-        }else {
-            panic!("Can't use session without godown")
         }
 
         session_items
     }
 
+    // Moves up to steps goods down into holes of the godown. Every
+    // moved item comes back with its old and new godown address, so its
+    // owner can relocate the token. Only goods that decode into an item
+    // the owner can place are moved, the rest stay where they are.
+    pub fn compact<F>(&mut self, steps: usize, movable: F) -> Result<Vec<(GodownAddress, GodownAddress, Item)>, Fumble>
+    where F: Fn(&Item) -> bool
+    {
+        let mut moved: Vec<(GodownAddress, GodownAddress, Item)> = Vec::new();
+
+        if let Some(godown) = self.o_godown.as_mut() {
+            let moves = godown.compact(steps, |good| {
+                match Item::from_good(good.clone()) {
+                    Ok(item) => movable(&item),
+                    Err(_) => false
+                }
+            })?;
+
+            for (source, target, good) in moves {
+                moved.push((source, target, Item::from_good(good).ok().unwrap()));
+            }
+        }
+
+        Ok(moved)
+    }
+
+    pub fn sync_due(&mut self) -> Result<(), Fumble> {
+        match self.o_godown.as_mut() {
+            Some(godown) => godown.sync_due(),
+            None => Ok(())
+        }
+    }
+
+    pub fn get_sync_wait(&self) -> Option<Duration> {
+        self.o_godown.as_ref().and_then(|godown| godown.get_sync_wait())
+    }
+
+    // Memory is never compacted, only the godown
+    pub fn is_hollow(&self) -> bool {
+        match self.o_godown.as_ref() {
+            Some(godown) => godown.is_hollow(),
+            None => false
+        }
+    }
+
+    // Only what is in the godown can outlast the process
+    pub fn is_durable(&self) -> bool {
+        match self.o_godown.as_ref() {
+            Some(godown) => godown.is_durable(),
+            None => false
+        }
+    }
+
     pub fn stop_session(&mut self) {
         if self.o_logistics.is_none() {
             panic!("Session is not running")
@@ -269,4 +357,85 @@ impl<Size: WholeNumber, Item: Merchandise> Warehouse<Size, Item> {
             self.o_logistics = None;
         }
     }
-}
\ No newline at end of file
+}
+
+// Name a file is rewritten under before it takes the old one's place
+pub fn rewrite_file_name(file_name: &str) -> String {
+    format!("{}.tmp", file_name)
+}
+
+// Name the old file is kept under once a rewritten one takes its place
+pub fn legacy_file_name(file_name: &str) -> String {
+    format!("{}.legacy", file_name)
+}
+
+// Passes every good in the file through convert into a fresh file
+// beside it, which swap then puts in place of the old one. In between
+// the caller notes the rewrite as done, so a crash before that rewrites
+// again and a crash after only swaps. Removed goods are left behind,
+// and so are corrupted ones and any convert refuses, as the new file
+// could not read them. Gives how many goods were left behind that way,
+// which are still in the old file swap keeps.
+pub fn rewrite<F>(file_name: &str, convert: F) -> Result<usize, Fumble>
+where F: Fn(&Good) -> Result<Good, Fumble>
+{
+    let temp_name = rewrite_file_name(file_name);
+
+    // left over from a rewrite cut short
+    setup::remove_file(&temp_name);
+    setup::remove_file(&ledger_file_name(&temp_name));
+
+    let mut godown = Godown::new(file_name);
+    let mut rewritten = Godown::new(&temp_name);
+    let mut logistics = Logistics::new(SessionMode::Uninitialize);
+    let mut dropped = 0;
+
+    while !logistics.get_has_done() {
+        godown.transfer_chunk(&mut logistics);
+
+        for logistics_item in logistics.unload() {
+            let converted = match logistics_item {
+                LogisticsItem::WithoutAddress(good) => convert(&good),
+                _ => Err(Fumble::Corrupted)
+            };
+
+            if let Ok(good) = converted {
+                if let Err(err) = rewritten.place(good) {
+                    drop(rewritten);
+                    setup::remove_file(&temp_name);
+                    setup::remove_file(&ledger_file_name(&temp_name));
+
+                    return Err(err);
+                }
+            }else {
+                dropped += 1;
+            }
+        }
+    }
+
+    drop(godown);
+    // Both ledgers are empty once closed
+    drop(rewritten);
+    setup::remove_file(&ledger_file_name(&temp_name));
+
+    Ok(dropped)
+}
+
+// Puts a rewritten file, if there is one, in place of the old one.
+// Its ledger goes too, as a rewrite cut short may have left it. The
+// old file is moved aside rather than removed, as it still holds
+// any good the rewrite left behind. A swap cut short between the
+// two renames finds the old one gone and only puts the new one in.
+pub fn swap(file_name: &str) {
+    let temp_name = rewrite_file_name(file_name);
+
+    setup::remove_file(&ledger_file_name(&temp_name));
+
+    if setup::has_file(&temp_name) {
+        if setup::has_file(file_name) {
+            setup::rename_file(file_name, &legacy_file_name(file_name));
+        }
+
+        setup::rename_file(&temp_name, file_name);
+    }
+}
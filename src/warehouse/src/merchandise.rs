@@ -1,6 +1,9 @@
+use fumble::Fumble;
 type Good = Vec<u8>;
 
 pub trait Merchandise: Clone {
     fn to_good(&self) -> Good;
-    fn from_good(good: Good) -> Self; 
+    // Good may have been corrupted on disk. So it should
+    // be refused with error rather than panic.
+    fn from_good(good: Good) -> Result<Self, Fumble>; 
 }
\ No newline at end of file